@@ -0,0 +1,219 @@
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+use crate::error::ResolveError;
+use crate::resolver::Match;
+
+// A rule from the blocklist or allowlist in the config file.
+// A rule without a name or regex matches every target of its kind.
+#[derive(Debug, Deserialize)]
+pub struct AccessRule {
+  // e.g. "channel", "channel_videos", "video", "clip" or "category", matches any kind if omitted
+  kind: Option<String>,
+  // Exact channel name, video id or clip slug (case-insensitive)
+  name: Option<String>,
+  // Case-insensitive like name
+  regex: Option<String>,
+  // Only used by the blocklist
+  status: Option<u16>,
+  message: Option<String>,
+}
+
+pub struct Rule {
+  kind: Option<String>,
+  name: Option<String>,
+  regex: Option<Regex>,
+  status: u16,
+  message: String,
+}
+
+impl Rule {
+  fn new(rule: &AccessRule) -> Rule {
+    Rule {
+      kind: rule.kind.clone(),
+      name: rule.name.clone(),
+      regex: rule.regex.as_ref().map(|re| {
+        RegexBuilder::new(re)
+          .case_insensitive(true)
+          .build()
+          .unwrap_or_else(|e| panic!("invalid regex {:?}: {}", re, e))
+      }),
+      status: match rule.status {
+        None => 403,
+        Some(status) if (400..=599).contains(&status) => status,
+        Some(status) => panic!(
+          "invalid status {} for {:?}, it must be 400-599",
+          status, rule
+        ),
+      },
+      message: rule
+        .message
+        .clone()
+        .unwrap_or(String::from("access denied")),
+    }
+  }
+
+  fn matches(&self, kind: &str, target: &str) -> bool {
+    if self.kind.as_ref().is_some_and(|k| k != kind) {
+      return false;
+    }
+    if self
+      .name
+      .as_ref()
+      .is_some_and(|name| !name.eq_ignore_ascii_case(target))
+    {
+      return false;
+    }
+    if self.regex.as_ref().is_some_and(|re| !re.is_match(target)) {
+      return false;
+    }
+    return true;
+  }
+}
+
+pub struct Rules {
+  blocklist: Vec<Rule>,
+  allowlist: Vec<Rule>,
+  // What everything that doesn't match an allow rule is denied with
+  allowlist_status: u16,
+  allowlist_message: String,
+}
+
+impl Rules {
+  fn new(
+    blocklist: &[AccessRule],
+    allowlist: &[AccessRule],
+    allowlist_status: u16,
+    allowlist_message: &str,
+  ) -> Rules {
+    if !(400..=599).contains(&allowlist_status) {
+      panic!(
+        "invalid status {} for allowlist_status, it must be 400-599",
+        allowlist_status
+      );
+    }
+    Rules {
+      blocklist: blocklist.iter().map(Rule::new).collect(),
+      allowlist: allowlist.iter().map(Rule::new).collect(),
+      allowlist_status,
+      allowlist_message: allowlist_message.to_string(),
+    }
+  }
+
+  // Allow rules take precedence over block rules. If there are allow rules, everything else is denied, so the
+  // blocklist is only consulted when the allowlist is empty.
+  fn check(&self, kind: &str, target: &str) -> Result<(), ResolveError> {
+    if self.allowlist.iter().any(|rule| rule.matches(kind, target)) {
+      return Ok(());
+    }
+    if !self.allowlist.is_empty() {
+      return Err(ResolveError::Blocked {
+        status: self.allowlist_status,
+        message: self.allowlist_message.clone(),
+      });
+    }
+    if let Some(rule) = self
+      .blocklist
+      .iter()
+      .find(|rule| rule.matches(kind, target))
+    {
+      return Err(ResolveError::Blocked {
+        status: rule.status,
+        message: rule.message.clone(),
+      });
+    }
+    return Ok(());
+  }
+}
+
+pub static RULES: LazyLock<Rules> = LazyLock::new(|| {
+  Rules::new(
+    &crate::CONFIG.blocklist,
+    &crate::CONFIG.allowlist,
+    crate::CONFIG.allowlist_status,
+    &crate::CONFIG.allowlist_message,
+  )
+});
+
+pub fn check(m: &Match) -> Result<(), ResolveError> {
+  let ret = RULES.check(m.kind(), m.target());
+  if let Err(e) = &ret {
+    log::info!("blocked {} {}: {}", m.kind(), m.target(), e);
+  }
+  return ret;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rule(kind: Option<&str>, name: Option<&str>, regex: Option<&str>) -> AccessRule {
+    AccessRule {
+      kind: kind.map(String::from),
+      name: name.map(String::from),
+      regex: regex.map(String::from),
+      status: Some(402),
+      message: Some(String::from("payment required")),
+    }
+  }
+
+  #[test]
+  fn test() {
+    let rules = Rules::new(
+      &[
+        rule(Some("channel"), Some("twit"), None),
+        rule(Some("clip"), None, Some("^Bad")),
+        rule(Some("video"), None, None),
+      ],
+      &[],
+      403,
+      "access denied",
+    );
+
+    assert!(rules.check("channel", "speedgaming").is_ok());
+    assert!(rules.check("channel_videos", "twit").is_ok());
+    assert!(rules.check("clip", "GoodClip").is_ok());
+
+    match rules.check("channel", "TWiT") {
+      Err(ResolveError::Blocked { status, message }) => {
+        assert_eq!(status, 402);
+        assert_eq!(message, "payment required");
+      }
+      ret => panic!("unexpected result: {:?}", ret),
+    }
+    assert!(rules.check("clip", "BadClip").is_err());
+    assert!(rules.check("clip", "badclip").is_err());
+    assert!(rules.check("video", "123").is_err());
+
+    // Allow rules take precedence, and only targets matching an allow rule are allowed when there are any
+    let rules = Rules::new(
+      &[rule(Some("video"), None, None)],
+      &[
+        rule(Some("video"), Some("113837699"), None),
+        rule(Some("channel"), Some("gamesdonequick"), None),
+      ],
+      451,
+      "only GamesDoneQuick",
+    );
+    assert!(rules.check("video", "113837699").is_ok());
+    assert!(rules.check("channel", "GamesDoneQuick").is_ok());
+    assert!(rules.check("clip", "GoodClip").is_err());
+    match rules.check("channel", "speedgaming") {
+      Err(ResolveError::Blocked { status, message }) => {
+        assert_eq!(status, 451);
+        assert_eq!(message, "only GamesDoneQuick");
+      }
+      ret => panic!("unexpected result: {:?}", ret),
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "invalid status")]
+  fn test_invalid_status() {
+    Rule::new(&AccessRule {
+      status: Some(301),
+      ..rule(None, None, None)
+    });
+  }
+}
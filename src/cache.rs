@@ -0,0 +1,164 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, Mutex},
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::resolver::Match;
+use crate::{Playlist, PlaylistItem};
+
+// Don't hand out tokens that are about to expire
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+  Hit,
+  Miss,
+  // Shared the result of a concurrent request for the same match
+  Coalesced,
+}
+
+impl CacheStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      CacheStatus::Hit => "HIT",
+      CacheStatus::Miss => "MISS",
+      CacheStatus::Coalesced => "COALESCED",
+    }
+  }
+}
+
+struct Entry {
+  playlist: Playlist,
+  expires: Instant,
+}
+
+pub struct Cache {
+  entries: Mutex<HashMap<Match, Entry>>,
+  max_entries: usize,
+}
+
+pub static CACHE: LazyLock<Cache> = LazyLock::new(|| Cache::new(crate::CONFIG.cache_max_entries));
+
+impl Cache {
+  pub fn new(max_entries: usize) -> Cache {
+    Cache {
+      entries: Mutex::new(HashMap::new()),
+      max_entries,
+    }
+  }
+
+  pub fn get(&self, m: &Match) -> Option<Playlist> {
+    let mut entries = self.entries.lock().unwrap();
+    let entry = entries.get(m)?;
+    if entry.expires <= Instant::now() {
+      entries.remove(m);
+      return None;
+    }
+    return Some(entry.playlist.clone());
+  }
+
+  pub fn insert(&self, m: Match, playlist: &Playlist) {
+    if self.max_entries == 0 {
+      return;
+    }
+    let ttl = match ttl(&m, &playlist.items) {
+      Some(v) => v,
+      None => return,
+    };
+    let now = Instant::now();
+
+    let mut entries = self.entries.lock().unwrap();
+    if entries.len() >= self.max_entries && !entries.contains_key(&m) {
+      entries.retain(|_, entry| entry.expires > now);
+      if entries.len() >= self.max_entries {
+        // Evict the entry that would expire first
+        let oldest = entries
+          .iter()
+          .min_by_key(|(_, entry)| entry.expires)
+          .map(|(k, _)| k.clone());
+        if let Some(k) = oldest {
+          entries.remove(&k);
+        }
+      }
+    }
+    // Adding the variants to a cached playlist doesn't extend how long it is cached
+    let expires = entries
+      .get(&m)
+      .map(|entry| entry.expires)
+      .filter(|expires| *expires > now)
+      .unwrap_or(now + ttl);
+    entries.insert(
+      m,
+      Entry {
+        playlist: playlist.clone(),
+        expires,
+      },
+    );
+  }
+}
+
+// The TTL is configured per kind, but is shortened if an access token in the playlist expires sooner
+fn ttl(m: &Match, playlist: &[PlaylistItem]) -> Option<Duration> {
+  let mut ttl = Duration::from_secs(*crate::CONFIG.cache_ttl.get(m.kind())?);
+
+  let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default();
+  for item in playlist.iter() {
+    if let Some(expires_at) = item.expires_at {
+      let remaining = Duration::from_secs(expires_at)
+        .saturating_sub(now)
+        .saturating_sub(EXPIRY_MARGIN);
+      ttl = ttl.min(remaining);
+    }
+  }
+
+  if ttl.is_zero() {
+    return None;
+  }
+  return Some(ttl);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::twitch::TwitchMatch;
+  use crate::ItemKind;
+
+  fn item(expires_at: Option<u64>) -> PlaylistItem {
+    PlaylistItem {
+      expires_at,
+      ..PlaylistItem::test(
+        ItemKind::Vod,
+        "https://usher.ttvnw.net/vod/113837699.m3u8",
+        "AGDQ 2017",
+      )
+    }
+  }
+
+  #[test]
+  fn test() {
+    let cache = Cache::new(2);
+    let video = |id: &str| Match::new("twitch", TwitchMatch::Video(String::from(id)));
+    let now = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap()
+      .as_secs();
+
+    cache.insert(video("1"), &Playlist::from(vec![item(None)]));
+    assert!(cache.get(&video("1")).is_some());
+    assert!(cache.get(&video("2")).is_none());
+
+    // Tokens that are about to expire are not cached
+    cache.insert(video("2"), &Playlist::from(vec![item(Some(now + 30))]));
+    assert!(cache.get(&video("2")).is_none());
+
+    // The entry that expires first is evicted when the cache is full
+    cache.insert(video("3"), &Playlist::from(vec![item(Some(now + 300))]));
+    cache.insert(video("4"), &Playlist::from(vec![item(None)]));
+    assert!(cache.get(&video("1")).is_some());
+    assert!(cache.get(&video("3")).is_none());
+    assert!(cache.get(&video("4")).is_some());
+  }
+}
@@ -0,0 +1,69 @@
+use actix_web::rt::time::sleep;
+use reqwest::{Client, RequestBuilder, Response};
+use std::{
+  sync::LazyLock,
+  time::{Duration, Instant},
+};
+
+use crate::error::ResolveError;
+use crate::metrics;
+
+// Shared between all requests so that connections to Twitch are reused
+pub static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+  Client::builder()
+    .connect_timeout(Duration::from_secs(crate::CONFIG.http_connect_timeout))
+    .read_timeout(Duration::from_secs(crate::CONFIG.http_read_timeout))
+    .pool_idle_timeout(Duration::from_secs(90))
+    .build()
+    .expect("build reqwest client")
+});
+
+// Sends the request, retrying on timeouts, connection errors and 5xx responses.
+// upstream names the service in the metrics, e.g. "graphql" or "usher".
+pub async fn send(upstream: &str, request: RequestBuilder) -> Result<Response, ResolveError> {
+  let mut attempt = 0;
+  loop {
+    let retries_left = attempt < crate::CONFIG.http_retries;
+    let start = Instant::now();
+    let ret = request
+      .try_clone()
+      .expect("request body must be cloneable")
+      .send()
+      .await;
+    metrics::UPSTREAM_DURATION
+      .with_label_values(&[upstream])
+      .observe(start.elapsed().as_secs_f64());
+    let status = match &ret {
+      Ok(response) => response.status().as_u16().to_string(),
+      Err(_) => String::from("error"),
+    };
+    metrics::UPSTREAM_REQUESTS
+      .with_label_values(&[upstream, status.as_str()])
+      .inc();
+    match ret {
+      Ok(response) => {
+        if !response.status().is_server_error() || !retries_left {
+          return Ok(response);
+        }
+        log::warn!("received {}, retrying", response.status());
+      }
+      Err(e) => {
+        if !(e.is_timeout() || e.is_connect()) || !retries_left {
+          log::error!("request error: {:?}", e);
+          return Err(ResolveError::Upstream(String::from(
+            "error sending request to Twitch",
+          )));
+        }
+        log::warn!("request error: {}, retrying", e);
+      }
+    }
+    attempt += 1;
+    sleep(backoff(attempt)).await;
+  }
+}
+
+// Exponential backoff with up to 100% jitter, e.g. 250-500 ms, 500-1000 ms, ...
+fn backoff(attempt: u32) -> Duration {
+  let delay = crate::CONFIG.http_retry_delay << (attempt - 1).min(10);
+  return Duration::from_millis(delay + fastrand::u64(0..=delay));
+}
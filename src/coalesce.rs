@@ -0,0 +1,118 @@
+use std::{
+  collections::HashMap,
+  future::Future,
+  sync::{Arc, LazyLock, Mutex},
+};
+use tokio::sync::OnceCell;
+
+use crate::error::ResolveError;
+use crate::resolver::Match;
+use crate::Playlist;
+
+type Shared = Arc<OnceCell<Result<Playlist, ResolveError>>>;
+
+// Lets concurrent requests for the same match share a single upstream request
+pub struct Coalescer {
+  inflight: Mutex<HashMap<Match, Shared>>,
+}
+
+pub static INFLIGHT: LazyLock<Coalescer> = LazyLock::new(Coalescer::new);
+
+// Removes the entry when the first request finishes or is cancelled
+struct Guard<'a> {
+  coalescer: &'a Coalescer,
+  m: &'a Match,
+  cell: Shared,
+}
+
+impl Drop for Guard<'_> {
+  fn drop(&mut self) {
+    let mut inflight = self.coalescer.inflight.lock().unwrap();
+    if inflight
+      .get(self.m)
+      .is_some_and(|cell| Arc::ptr_eq(cell, &self.cell))
+    {
+      inflight.remove(self.m);
+    }
+  }
+}
+
+impl Coalescer {
+  fn new() -> Coalescer {
+    Coalescer {
+      inflight: Mutex::new(HashMap::new()),
+    }
+  }
+
+  // Runs f unless a request for the same match is already in flight, in which case its result is shared.
+  // Returns true as the second value if the result came from another request.
+  pub async fn run<F, Fut>(&self, m: &Match, f: F) -> (Result<Playlist, ResolveError>, bool)
+  where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Playlist, ResolveError>>,
+  {
+    let (cell, guard) = {
+      let mut inflight = self.inflight.lock().unwrap();
+      match inflight.get(m) {
+        Some(cell) => (cell.clone(), None),
+        None => {
+          let cell = Shared::default();
+          inflight.insert(m.clone(), cell.clone());
+          let guard = Guard {
+            coalescer: self,
+            m,
+            cell: cell.clone(),
+          };
+          (cell, Some(guard))
+        }
+      }
+    };
+
+    let mut coalesced = true;
+    let ret = cell
+      .get_or_init(|| {
+        coalesced = false;
+        f()
+      })
+      .await
+      .clone();
+    drop(guard);
+    return (ret, coalesced);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::twitch::TwitchMatch;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  use tokio::sync::Notify;
+
+  #[actix_web::test]
+  async fn test() {
+    let coalescer = Coalescer::new();
+    let m = Match::new("twitch", TwitchMatch::Channel(String::from("speedgaming")));
+    let calls = AtomicUsize::new(0);
+    let notify = Notify::new();
+
+    let f = || async {
+      calls.fetch_add(1, Ordering::SeqCst);
+      notify.notified().await;
+      Ok(Playlist::from(vec![]))
+    };
+    let (first, second, _) = tokio::join!(coalescer.run(&m, f), coalescer.run(&m, f), async {
+      notify.notify_one();
+    });
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(!first.1);
+    assert!(second.1);
+    assert!(coalescer.inflight.lock().unwrap().is_empty());
+
+    // Later requests start over
+    let (_, coalesced) = coalescer
+      .run(&m, || async { Ok(Playlist::from(vec![])) })
+      .await;
+    assert!(!coalesced);
+  }
+}
@@ -0,0 +1,73 @@
+use actix_web::http::StatusCode;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum ResolveError {
+  // The channel, video or clip does not exist
+  NotFound(String),
+  // The channel exists but is not currently streaming
+  NotLive(String),
+  // Twitch returned something we didn't expect
+  Upstream(String),
+  // The response from Twitch could not be deserialized
+  Deserialize(String),
+  // Blocked by us, the status is chosen by the rule that matched
+  Blocked { status: u16, message: String },
+  // Something is wrong with our configuration
+  Misconfigured(String),
+  // Either we or Twitch are rate limiting, with an optional delay in seconds
+  RateLimited(Option<u64>),
+  // The request can't be fulfilled, e.g. an output format that doesn't apply to the URL
+  Unsupported(String),
+  // The client is older than min_client_version
+  UpgradeRequired(String),
+}
+
+impl ResolveError {
+  pub fn status(&self) -> StatusCode {
+    match self {
+      ResolveError::NotFound(_) => StatusCode::NOT_FOUND,
+      ResolveError::NotLive(_) => StatusCode::NOT_FOUND,
+      ResolveError::Upstream(_) => StatusCode::BAD_GATEWAY,
+      ResolveError::Deserialize(_) => StatusCode::BAD_GATEWAY,
+      ResolveError::Blocked { status, .. } => {
+        StatusCode::from_u16(*status).unwrap_or(StatusCode::FORBIDDEN)
+      }
+      ResolveError::Misconfigured(_) => StatusCode::INTERNAL_SERVER_ERROR,
+      ResolveError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+      ResolveError::Unsupported(_) => StatusCode::BAD_REQUEST,
+      ResolveError::UpgradeRequired(_) => StatusCode::UPGRADE_REQUIRED,
+    }
+  }
+
+  // Machine-readable error code included in JSON responses
+  pub fn code(&self) -> &'static str {
+    match self {
+      ResolveError::NotFound(_) => "not_found",
+      ResolveError::NotLive(_) => "not_live",
+      ResolveError::Upstream(_) => "upstream_error",
+      ResolveError::Deserialize(_) => "deserialization_error",
+      ResolveError::Blocked { .. } => "blocked",
+      ResolveError::Misconfigured(_) => "misconfigured",
+      ResolveError::RateLimited(_) => "rate_limited",
+      ResolveError::Unsupported(_) => "unsupported",
+      ResolveError::UpgradeRequired(_) => "upgrade_required",
+    }
+  }
+}
+
+impl fmt::Display for ResolveError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ResolveError::NotFound(message)
+      | ResolveError::NotLive(message)
+      | ResolveError::Upstream(message)
+      | ResolveError::Deserialize(message)
+      | ResolveError::Blocked { message, .. }
+      | ResolveError::Misconfigured(message)
+      | ResolveError::Unsupported(message)
+      | ResolveError::UpgradeRequired(message) => write!(f, "{}", message),
+      ResolveError::RateLimited(_) => write!(f, "rate limited"),
+    }
+  }
+}
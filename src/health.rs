@@ -0,0 +1,63 @@
+use actix_web::{get, HttpResponse};
+use serde_json::json;
+use std::{
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+use crate::resolver;
+
+// Avoid sending a request to Twitch every time the load balancer checks on us
+const READY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+static READY: Mutex<Option<(Instant, Result<(), String>)>> = Mutex::new(None);
+
+// The process is up and able to respond
+#[get("/healthz")]
+async fn healthz() -> HttpResponse {
+  return HttpResponse::Ok().json(json!({
+    "status": "ok",
+  }));
+}
+
+// All resolvers are able to talk to their upstream services
+#[get("/readyz")]
+async fn readyz() -> HttpResponse {
+  let ret = match cached() {
+    Some(ret) => ret,
+    None => {
+      let ret = check().await;
+      *READY.lock().unwrap() = Some((Instant::now(), ret.clone()));
+      ret
+    }
+  };
+
+  return match ret {
+    Ok(()) => HttpResponse::Ok().json(json!({
+      "status": "ok",
+    })),
+    Err(reason) => HttpResponse::ServiceUnavailable().json(json!({
+      "status": "degraded",
+      "reason": reason,
+    })),
+  };
+}
+
+fn cached() -> Option<Result<(), String>> {
+  let ready = READY.lock().unwrap();
+  let (checked_at, ret) = ready.as_ref()?;
+  if checked_at.elapsed() >= READY_CACHE_TTL {
+    return None;
+  }
+  return Some(ret.clone());
+}
+
+async fn check() -> Result<(), String> {
+  for resolver in resolver::resolvers().iter() {
+    if let Err(e) = resolver.health_check().await {
+      log::warn!("{} health check failed: {}", resolver.name(), e);
+      return Err(format!("{}: {}", resolver.name(), e));
+    }
+  }
+  return Ok(());
+}
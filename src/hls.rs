@@ -0,0 +1,281 @@
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::client;
+use crate::error::ResolveError;
+use crate::{ItemKind, Playlist, PlaylistItem};
+
+// A media playlist listed in an HLS master playlist
+#[derive(Debug, Clone, Serialize)]
+pub struct Variant {
+  name: String, // e.g. "720p60", "1080p60 (source)" or "audio_only"
+  group_id: Option<String>,
+  resolution: Option<String>, // e.g. "1280x720"
+  framerate: Option<f64>,
+  bandwidth: Option<u64>, // bits per second
+  codecs: Option<String>,
+  url: String,
+}
+
+impl Variant {
+  fn is_audio_only(&self) -> bool {
+    return self.group_id.as_deref() == Some("audio_only") || self.name == "audio_only";
+  }
+
+  // "1080p60 (source)" is matched by "1080p60" and "source"
+  fn matches(&self, quality: &str) -> bool {
+    let name = self.name.to_lowercase();
+    let short_name = name.split(' ').next().unwrap_or_default();
+    return short_name == quality
+      || self.group_id.as_deref() == Some(quality)
+      || (quality == "source"
+        && (name.contains("(source)") || self.group_id.as_deref() == Some("chunked")));
+  }
+}
+
+// Picks a variant by name, "best", "worst" or "audio_only". A resolution without a framerate (e.g. "720p") picks the
+// variant with the highest bandwidth with that resolution.
+pub fn select<'a>(variants: &'a [Variant], quality: &str) -> Option<&'a Variant> {
+  let quality = quality.trim().to_lowercase();
+  let bandwidth = |variant: &&Variant| variant.bandwidth.unwrap_or_default();
+  let video = variants.iter().filter(|variant| !variant.is_audio_only());
+  match quality.as_str() {
+    "best" => video.max_by_key(bandwidth),
+    "worst" => video.min_by_key(bandwidth),
+    "audio_only" | "audio" => variants.iter().find(|variant| variant.is_audio_only()),
+    _ => variants
+      .iter()
+      .find(|variant| variant.matches(&quality))
+      .or_else(|| {
+        video
+          .filter(|variant| variant.name.to_lowercase().starts_with(&quality))
+          .max_by_key(bandwidth)
+      }),
+  }
+}
+
+// https://datatracker.ietf.org/doc/html/rfc8216#section-4.3.4.2
+pub fn parse_master(base_url: &Url, master: &str) -> Vec<Variant> {
+  // The names of the renditions are in EXT-X-MEDIA tags, keyed by their GROUP-ID
+  let mut names = HashMap::new();
+  let mut variants = Vec::new();
+  let mut stream_inf: Option<HashMap<String, String>> = None;
+
+  for line in master
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty())
+  {
+    if let Some(attributes) = line.strip_prefix("#EXT-X-MEDIA:") {
+      let attributes = parse_attributes(attributes);
+      if let (Some(group_id), Some(name)) = (attributes.get("GROUP-ID"), attributes.get("NAME")) {
+        names.insert(group_id.clone(), name.clone());
+      }
+    } else if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+      stream_inf = Some(parse_attributes(attributes));
+    } else if line.starts_with('#') {
+      continue;
+    } else if let Some(attributes) = stream_inf.take() {
+      let url = match base_url.join(line) {
+        Ok(url) => url.to_string(),
+        Err(_) => continue,
+      };
+      let group_id = attributes.get("VIDEO").cloned();
+      let resolution = attributes.get("RESOLUTION").cloned();
+      let framerate = attributes
+        .get("FRAME-RATE")
+        .and_then(|v| v.parse::<f64>().ok());
+      let name = group_id
+        .as_ref()
+        .and_then(|group_id| names.get(group_id).cloned())
+        .or_else(|| {
+          let (_, height) = resolution.as_ref()?.split_once('x')?;
+          return Some(format!(
+            "{}p{}",
+            height,
+            framerate.map(|f| f.round().to_string()).unwrap_or_default()
+          ));
+        })
+        .or_else(|| group_id.clone())
+        .unwrap_or_else(|| format!("variant{}", variants.len() + 1));
+      variants.push(Variant {
+        name,
+        group_id,
+        resolution,
+        framerate,
+        bandwidth: attributes.get("BANDWIDTH").and_then(|v| v.parse().ok()),
+        codecs: attributes.get("CODECS").cloned(),
+        url,
+      });
+    }
+  }
+  return variants;
+}
+
+// Attributes are comma separated, but quoted values can contain commas
+fn parse_attributes(s: &str) -> HashMap<String, String> {
+  let mut attributes = HashMap::new();
+  let mut rest = s;
+  while let Some((key, value)) = rest.split_once('=') {
+    let (value, remaining) = match value.strip_prefix('"') {
+      Some(quoted) => {
+        let (value, remaining) = quoted.split_once('"').unwrap_or((quoted, ""));
+        (
+          value,
+          remaining.split_once(',').map(|(_, r)| r).unwrap_or(""),
+        )
+      }
+      None => value.split_once(',').unwrap_or((value, "")),
+    };
+    attributes.insert(key.trim().to_string(), value.to_string());
+    rest = remaining;
+  }
+  return attributes;
+}
+
+// The URL of the item if it is the master playlist of a live stream or video
+fn master_url(item: &PlaylistItem) -> Option<Url> {
+  if !matches!(item.kind, ItemKind::Live | ItemKind::Vod) {
+    return None;
+  }
+  return Url::parse(&item.path)
+    .ok()
+    .filter(|url| url.path().ends_with(".m3u8"));
+}
+
+// True if a master playlist in the playlist hasn't been fetched yet
+pub fn needs_variants(playlist: &Playlist) -> bool {
+  return playlist
+    .items
+    .iter()
+    .filter_map(master_url)
+    .any(|url| !playlist.variants.contains_key(url.as_str()));
+}
+
+// Fetches the master playlists that haven't been fetched yet. This is done when resolving, so that the variants are
+// cached and shared with concurrent requests like the rest of the playlist.
+pub async fn load_variants(playlist: &mut Playlist) -> Result<(), ResolveError> {
+  let urls: Vec<_> = playlist.items.iter().filter_map(master_url).collect();
+  for url in urls {
+    if !playlist.variants.contains_key(url.as_str()) {
+      let variants = fetch_variants(&url).await?;
+      playlist.variants.insert(url.to_string(), variants);
+    }
+  }
+  return Ok(());
+}
+
+// Replaces the master playlists of live streams and videos with the media playlist of the requested quality, using
+// the variants from load_variants. All variants are listed on the item so that the JSON output can show what is
+// available.
+pub fn apply_quality(playlist: &mut Playlist, quality: &str) -> Result<(), ResolveError> {
+  for item in playlist.items.iter_mut() {
+    let variants = match master_url(item).and_then(|url| playlist.variants.get(url.as_str())) {
+      Some(variants) => variants.clone(),
+      None => continue,
+    };
+    let variant = select(&variants, quality).ok_or_else(|| {
+      ResolveError::NotFound(format!(
+        "quality {} is not available, use one of: best, worst, {}",
+        quality,
+        variants
+          .iter()
+          .map(|variant| variant.name.as_str())
+          .collect::<Vec<_>>()
+          .join(", ")
+      ))
+    })?;
+    item.path = variant.url.clone();
+    item.variants = Some(variants);
+  }
+  return Ok(());
+}
+
+async fn fetch_variants(url: &Url) -> Result<Vec<Variant>, ResolveError> {
+  let response = client::send("usher", client::CLIENT.get(url.clone())).await?;
+  let status = response.status();
+  if !status.is_success() {
+    log::error!("master playlist returned {}", status);
+    return Err(ResolveError::Upstream(format!(
+      "error fetching the master playlist ({})",
+      status.as_u16()
+    )));
+  }
+  let master = response.text().await.map_err(|e| {
+    log::error!("error reading master playlist: {:?}", e);
+    return ResolveError::Upstream(String::from("error reading the master playlist"));
+  })?;
+  if cfg!(debug_assertions) {
+    log::info!("master playlist: {}", master);
+  }
+  return Ok(parse_master(url, &master));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const MASTER: &str = r#"#EXTM3U
+#EXT-X-TWITCH-INFO:NODE="video-edge-c2a8b4.sjc02",SERVING-ID="abc"
+#EXT-X-MEDIA:TYPE=VIDEO,GROUP-ID="chunked",NAME="1080p60 (source)",AUTOSELECT=YES,DEFAULT=YES
+#EXT-X-STREAM-INF:BANDWIDTH=6000000,RESOLUTION=1920x1080,CODECS="avc1.64002A,mp4a.40.2",VIDEO="chunked",FRAME-RATE=60.000
+https://video-weaver.sjc02.hls.ttvnw.net/v1/playlist/chunked.m3u8
+#EXT-X-MEDIA:TYPE=VIDEO,GROUP-ID="720p60",NAME="720p60",AUTOSELECT=YES,DEFAULT=YES
+#EXT-X-STREAM-INF:BANDWIDTH=3000000,RESOLUTION=1280x720,CODECS="avc1.4D401F,mp4a.40.2",VIDEO="720p60",FRAME-RATE=60.000
+https://video-weaver.sjc02.hls.ttvnw.net/v1/playlist/720p60.m3u8
+#EXT-X-MEDIA:TYPE=VIDEO,GROUP-ID="160p30",NAME="160p",AUTOSELECT=YES,DEFAULT=YES
+#EXT-X-STREAM-INF:BANDWIDTH=230000,RESOLUTION=284x160,CODECS="avc1.4D401F,mp4a.40.2",VIDEO="160p30",FRAME-RATE=30.000
+160p30/index-dvr.m3u8
+#EXT-X-MEDIA:TYPE=VIDEO,GROUP-ID="audio_only",NAME="audio_only",AUTOSELECT=NO,DEFAULT=NO
+#EXT-X-STREAM-INF:BANDWIDTH=160000,CODECS="mp4a.40.2",VIDEO="audio_only"
+https://video-weaver.sjc02.hls.ttvnw.net/v1/playlist/audio_only.m3u8
+"#;
+
+  #[test]
+  fn test() {
+    let base_url = Url::parse("https://usher.ttvnw.net/vod/113837699.m3u8?sig=x").unwrap();
+    let variants = parse_master(&base_url, MASTER);
+    assert_eq!(variants.len(), 4);
+    assert_eq!(variants[0].name, "1080p60 (source)");
+    assert_eq!(variants[0].resolution.as_deref(), Some("1920x1080"));
+    assert_eq!(variants[0].framerate, Some(60.0));
+    assert_eq!(variants[0].bandwidth, Some(6000000));
+    assert_eq!(variants[0].codecs.as_deref(), Some("avc1.64002A,mp4a.40.2"));
+    assert_eq!(
+      variants[2].url,
+      "https://usher.ttvnw.net/vod/160p30/index-dvr.m3u8"
+    );
+
+    let name = |quality: &str| select(&variants, quality).map(|variant| variant.name.as_str());
+    assert_eq!(name("best"), Some("1080p60 (source)"));
+    assert_eq!(name("source"), Some("1080p60 (source)"));
+    assert_eq!(name("1080p60"), Some("1080p60 (source)"));
+    assert_eq!(name("worst"), Some("160p"));
+    assert_eq!(name("720P60"), Some("720p60"));
+    assert_eq!(name("720p"), Some("720p60"));
+    assert_eq!(name("160p30"), Some("160p"));
+    assert_eq!(name("audio_only"), Some("audio_only"));
+    assert_eq!(name("480p"), None);
+
+    // The quality is picked from the variants that were loaded with the playlist
+    let mut playlist = Playlist::from(vec![PlaylistItem::test(
+      ItemKind::Vod,
+      base_url.as_str(),
+      "AGDQ 2017",
+    )]);
+    assert!(needs_variants(&playlist));
+    assert!(apply_quality(&mut playlist.clone(), "720p").is_ok());
+    playlist
+      .variants
+      .insert(base_url.to_string(), variants.clone());
+    assert!(!needs_variants(&playlist));
+    assert!(matches!(
+      apply_quality(&mut playlist.clone(), "480p"),
+      Err(ResolveError::NotFound(_))
+    ));
+    apply_quality(&mut playlist, "720p").unwrap();
+    assert_eq!(playlist.items[0].path, variants[1].url);
+    assert_eq!(playlist.items[0].variants.as_ref().unwrap().len(), 4);
+  }
+}
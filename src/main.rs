@@ -1,11 +1,29 @@
+#![allow(clippy::needless_return)]
+
+pub mod access;
+pub mod cache;
+pub mod client;
+pub mod coalesce;
+pub mod error;
+pub mod health;
+pub mod hls;
+pub mod metrics;
+pub mod output;
+pub mod ratelimit;
+pub mod resolver;
 pub mod twitch;
+pub mod version;
+pub mod vlc;
 
-use actix_web::{get, middleware, web, App, HttpResponse, HttpServer};
+use actix_web::{get, http::header, middleware, web, App, HttpRequest, HttpResponse, HttpServer};
+use chrono::{DateTime, Utc};
 use config::Config;
-use env_logger;
+use error::ResolveError;
 use log::{self, info, warn};
-use serde::{Deserialize, Serialize};
+use output::Output;
+use serde::{Deserialize, Serialize, Serializer};
 use serde_json::json;
+use std::collections::HashMap;
 use std::env;
 use std::sync::LazyLock;
 
@@ -14,6 +32,28 @@ pub struct AppConfig {
   host: String,
   port: u16,
   twitch_client_id: Option<String>,
+  public_url: Option<String>,
+  http_connect_timeout: u64, // seconds
+  http_read_timeout: u64,    // seconds
+  http_retries: u32,
+  http_retry_delay: u64,           // milliseconds
+  cache_ttl: HashMap<String, u64>, // seconds per kind
+  cache_max_entries: usize,
+  rate_limit_per_client: u32, // requests per minute, 0 disables
+  rate_limit_client_burst: u32,
+  rate_limit_per_target: u32, // requests per minute, 0 disables
+  rate_limit_target_burst: u32,
+  trusted_proxy_header: Option<String>,
+  min_client_version: u32,         // 0 disables
+  recommended_client_version: u32, // 0 disables
+  upgrade_url: Option<String>,
+  #[serde(default)]
+  blocklist: Vec<access::AccessRule>,
+  #[serde(default)]
+  allowlist: Vec<access::AccessRule>,
+  // Returned for everything that doesn't match an allow rule
+  allowlist_status: u16,
+  allowlist_message: String,
 }
 
 pub static CONFIG: LazyLock<AppConfig> = LazyLock::new(|| {
@@ -22,6 +62,48 @@ pub static CONFIG: LazyLock<AppConfig> = LazyLock::new(|| {
     .unwrap()
     .set_default("port", 8080)
     .unwrap()
+    .set_default("http_connect_timeout", 5)
+    .unwrap()
+    .set_default("http_read_timeout", 10)
+    .unwrap()
+    .set_default("http_retries", 2)
+    .unwrap()
+    .set_default("http_retry_delay", 250)
+    .unwrap()
+    .set_default("cache_ttl.channel", 30)
+    .unwrap()
+    .set_default("cache_ttl.channel_videos", 300)
+    .unwrap()
+    .set_default("cache_ttl.channel_clips", 300)
+    .unwrap()
+    .set_default("cache_ttl.category", 60)
+    .unwrap()
+    .set_default("cache_ttl.category_clips", 300)
+    .unwrap()
+    .set_default("cache_ttl.category_videos", 300)
+    .unwrap()
+    .set_default("cache_ttl.video", 600)
+    .unwrap()
+    .set_default("cache_ttl.clip", 600)
+    .unwrap()
+    .set_default("cache_max_entries", 1000)
+    .unwrap()
+    .set_default("rate_limit_per_client", 0)
+    .unwrap()
+    .set_default("rate_limit_client_burst", 10)
+    .unwrap()
+    .set_default("rate_limit_per_target", 0)
+    .unwrap()
+    .set_default("rate_limit_target_burst", 10)
+    .unwrap()
+    .set_default("min_client_version", 0)
+    .unwrap()
+    .set_default("recommended_client_version", 0)
+    .unwrap()
+    .set_default("allowlist_status", 403)
+    .unwrap()
+    .set_default("allowlist_message", "access denied")
+    .unwrap()
     .add_source(config::File::with_name("media-resolver.toml").required(false))
     .add_source(config::Environment::default())
     .build()
@@ -34,10 +116,27 @@ pub static CONFIG: LazyLock<AppConfig> = LazyLock::new(|| {
 pub struct ResolveRequest {
   url: String,
   output: Option<String>,
-  // v: Option<String>,
+  // e.g. "720p60", "best", "worst" or "audio_only"
+  quality: Option<String>,
+  // Shorthand for quality=audio_only, e.g. audio_only=1
+  audio_only: Option<String>,
+  // The version of twitch.lua, which also decides the version of the JSON output
+  v: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+  Live,
+  Vod,
+  Clip,
+  // Links to the next page of a listing, e.g. "Load more"
+  Pagination,
+  // A message for the user, e.g. to upgrade twitch.lua
+  Notice,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PlaylistItem {
   path: String,
   name: String,
@@ -45,8 +144,80 @@ pub struct PlaylistItem {
   language: Option<String>,
   artist: Option<String>,
   genre: Option<String>,
-  date: Option<String>,
+  #[serde(serialize_with = "serialize_date")]
+  date: Option<DateTime<Utc>>,
   duration: Option<usize>, // seconds
+  // Where to start playing, in seconds. Only listed when set, so that the output stays the same for older clients.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  start_time: Option<usize>,
+  // Only listed when a quality is requested, so that the output stays the same for older clients
+  #[serde(skip_serializing_if = "Option::is_none")]
+  variants: Option<Vec<hls::Variant>>,
+  #[serde(skip)]
+  kind: ItemKind,
+  #[serde(skip)]
+  id: Option<String>, // stable identifier, e.g. the canonical URL of a video
+  #[serde(skip)]
+  expires_at: Option<u64>, // unix timestamp of when the access token expires
+}
+
+// What a resolver returns. Listings have a title and description of their own, since their items can come from
+// different channels.
+#[derive(Debug, Clone)]
+pub struct Playlist {
+  title: Option<String>,       // e.g. the display name of the channel or category
+  description: Option<String>, // e.g. "Clips in Super Mario 64"
+  items: Vec<PlaylistItem>,
+  // The variants of the master playlists in items by their URL, only fetched when a quality is requested
+  variants: HashMap<String, Vec<hls::Variant>>,
+}
+
+impl From<Vec<PlaylistItem>> for Playlist {
+  fn from(items: Vec<PlaylistItem>) -> Playlist {
+    Playlist {
+      title: None,
+      description: None,
+      items,
+      variants: HashMap::new(),
+    }
+  }
+}
+
+#[cfg(test)]
+impl PlaylistItem {
+  // Tests spell out the fields they care about and take the rest from here with ..PlaylistItem::test(..)
+  pub fn test(kind: ItemKind, path: &str, name: &str) -> PlaylistItem {
+    PlaylistItem {
+      path: String::from(path),
+      name: String::from(name),
+      description: None,
+      language: None,
+      artist: None,
+      genre: None,
+      date: None,
+      duration: None,
+      start_time: None,
+      variants: None,
+      kind,
+      id: None,
+      expires_at: None,
+    }
+  }
+}
+
+// Dates are formatted like "2017-01-10 20:10:16" (in UTC) since that is what VLC displays nicely
+pub fn format_date(date: &DateTime<Utc>) -> String {
+  return date.format("%Y-%m-%d %H:%M:%S").to_string();
+}
+
+fn serialize_date<S: Serializer>(
+  date: &Option<DateTime<Utc>>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  match date {
+    Some(date) => serializer.serialize_str(&format_date(date)),
+    None => serializer.serialize_none(),
+  }
 }
 
 #[actix_web::main]
@@ -57,15 +228,22 @@ async fn main() -> std::io::Result<()> {
   if CONFIG.twitch_client_id.is_none() {
     warn!("twitch_client_id has not been configured! Please edit media-resolver.toml and then restart the program.");
   }
+  LazyLock::force(&access::RULES);
 
   HttpServer::new(|| {
-    App::new().service(resolve).wrap(middleware::Logger::new(
-      env::var("ACCESS_LOG_FORMAT")
-        .unwrap_or(String::from(
-          r#"%{r}a "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T"#,
-        ))
-        .as_str(),
-    ))
+    App::new()
+      .service(resolve)
+      .service(metrics_endpoint)
+      .service(health::healthz)
+      .service(health::readyz)
+      .service(vlc::twitch_lua)
+      .wrap(middleware::Logger::new(
+        env::var("ACCESS_LOG_FORMAT")
+          .unwrap_or(String::from(
+            r#"%{r}a "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T"#,
+          ))
+          .as_str(),
+      ))
   })
   .bind((CONFIG.host.as_str(), CONFIG.port))?
   .run()
@@ -73,57 +251,146 @@ async fn main() -> std::io::Result<()> {
 }
 
 #[get("/resolve")]
-async fn resolve(web::Query(q): web::Query<ResolveRequest>) -> HttpResponse {
+async fn resolve(req: HttpRequest, web::Query(q): web::Query<ResolveRequest>) -> HttpResponse {
+  let mut response = resolve_response(&req, &q).await;
+  // The response depends on the Accept header unless output is used, but caches can't tell the difference
+  output::insert_header(&mut response, "vary", "Accept");
+  return response;
+}
+
+async fn resolve_response(req: &HttpRequest, q: &ResolveRequest) -> HttpResponse {
   if cfg!(debug_assertions) {
     log::info!("url: {}", q.url);
   }
   let url = q.url.as_str();
-  let output_string = q.output.unwrap_or_default();
-  let output = output_string.as_str();
-
-  // Potential future hard upgrade error:
-  // if q.v.is_some() && q.v.unwrap() == "1" {
-  //   return HttpResponse::Ok().json(json!({
-  //     "error": "Please upgrade your twitch.lua file!",
-  //   }));
-  // }
-
-  let m = twitch::probe(url);
-  if m.is_some() {
+  let accept = req
+    .headers()
+    .get(header::ACCEPT)
+    .and_then(|v| v.to_str().ok());
+  let output = Output::negotiate(q.output.as_deref(), accept);
+  // Only the VLC script asks for output=json explicitly, negotiated JSON gets the real status code
+  let errors_as_ok = Output::from_param(q.output.as_deref()) == Output::Json;
+
+  if let Err(e) = ratelimit::check_client(req) {
+    metrics::CLIENT_RATE_LIMITED.inc();
+    return error_response(&e, errors_as_ok);
+  }
+
+  metrics::CLIENT_VERSIONS
+    .with_label_values(&[&version::metrics_label(q.v.as_deref())])
+    .inc();
+  let client_version = version::parse(q.v.as_deref());
+  if let Err(e) = version::check(client_version) {
+    return error_response(&e, errors_as_ok);
+  }
+
+  if let Some((resolver, m)) = resolver::probe(url) {
     if cfg!(debug_assertions) {
-      log::info!("m: {:?}", m);
+      log::info!("{}: {:?}", resolver.name(), m);
     }
-    let playlist = match twitch::resolve(m.unwrap()).await {
+    if output.is_feed() && !m.is_listing() {
+      return error_response(
+        &ResolveError::Unsupported(format!(
+          "output={} is only supported for listings",
+          output.name()
+        )),
+        errors_as_ok,
+      );
+    }
+    let quality = match q.audio_only.as_deref() {
+      Some("0") | Some("false") | None => q.quality.as_deref(),
+      Some(_) => Some("audio_only"),
+    };
+    if quality.is_some() && !m.has_variants() {
+      return error_response(
+        &ResolveError::Unsupported(String::from(
+          "quality and audio_only are only supported for live streams and videos",
+        )),
+        errors_as_ok,
+      );
+    }
+    let (mut playlist, cache_status) = match resolver::resolve(resolver, m, quality.is_some()).await
+    {
       Ok(v) => v,
       Err(e) => {
         log::error!("error: {}", e);
-        let mut error_status = if output == "json" {
-          // VLC playlist parsers can't read the data of non-200 responses
-          HttpResponse::Ok()
-        } else if e == "payment required" {
-          HttpResponse::PaymentRequired()
-        } else {
-          HttpResponse::InternalServerError()
-        };
-        return error_status.json(json!({
-          "error": e,
-        }));
+        return error_response(&e, errors_as_ok);
       }
     };
+    if let Some(start_time) = resolver.start_time(url) {
+      for item in playlist
+        .items
+        .iter_mut()
+        .filter(|item| item.kind == ItemKind::Vod)
+      {
+        item.start_time = Some(start_time);
+      }
+    }
+    if let Some(quality) = quality {
+      if let Err(e) = hls::apply_quality(&mut playlist, quality) {
+        log::error!("error: {}", e);
+        return error_response(&e, errors_as_ok);
+      }
+    }
     if cfg!(debug_assertions) {
       log::info!("playlist: {:?}", playlist);
     }
 
-    if output == "json" {
-      return HttpResponse::Ok().json(playlist);
-    } else if playlist.len() > 0 {
-      return HttpResponse::TemporaryRedirect()
-        .append_header(("Location", playlist.first().unwrap().path.as_str()))
-        .finish();
-    } else {
-      return HttpResponse::NotFound().finish();
+    metrics::RESPONSES.with_label_values(&[output.name()]).inc();
+    let base_url = base_url(req);
+    if output.is_playlist() {
+      // Appended so that the notice isn't played first
+      playlist
+        .items
+        .extend(version::notice(client_version, &base_url));
     }
+    let ctx = output::Context {
+      url,
+      base_url,
+      version: if client_version.is_some_and(|v| v >= 2) {
+        2
+      } else {
+        1
+      },
+      title: playlist.title.as_deref(),
+      description: playlist.description.as_deref(),
+    };
+    let mut response = output::render(output, &playlist.items, &ctx);
+    output::insert_header(&mut response, "x-cache", cache_status.as_str());
+    return response;
   }
 
   return HttpResponse::NotFound().finish();
 }
+
+#[get("/metrics")]
+async fn metrics_endpoint() -> HttpResponse {
+  return HttpResponse::Ok()
+    .content_type("text/plain; version=0.0.4")
+    .body(metrics::render());
+}
+
+// The public URL of this server, used to link back to /resolve
+pub fn base_url(req: &HttpRequest) -> String {
+  if let Some(public_url) = CONFIG.public_url.as_ref() {
+    return public_url.trim_end_matches('/').to_string();
+  }
+  let conn = req.connection_info();
+  return format!("{}://{}", conn.scheme(), conn.host());
+}
+
+fn error_response(e: &ResolveError, errors_as_ok: bool) -> HttpResponse {
+  let mut response = if errors_as_ok {
+    // VLC playlist parsers can't read the data of non-200 responses
+    HttpResponse::Ok()
+  } else {
+    HttpResponse::build(e.status())
+  };
+  if let ResolveError::RateLimited(Some(retry_after)) = e {
+    response.append_header(("Retry-After", retry_after.to_string()));
+  }
+  return response.json(json!({
+    "error": e.to_string(),
+    "code": e.code(),
+  }));
+}
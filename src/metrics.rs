@@ -0,0 +1,88 @@
+use prometheus::{
+  register_histogram_vec, register_int_counter, register_int_counter_vec, Encoder, HistogramVec,
+  IntCounter, IntCounterVec, TextEncoder,
+};
+use std::sync::LazyLock;
+
+// Outcome is "ok" or the error code
+pub static RESOLVES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+  register_int_counter_vec!(
+    "media_resolver_resolves_total",
+    "Number of resolves by provider, kind and outcome.",
+    &["provider", "kind", "outcome"]
+  )
+  .unwrap()
+});
+
+pub static RESOLVE_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+  register_histogram_vec!(
+    "media_resolver_resolve_duration_seconds",
+    "Time taken to resolve a URL, including cache hits.",
+    &["provider", "kind"]
+  )
+  .unwrap()
+});
+
+// Upstream is the service that was requested, e.g. "graphql" or "usher".
+// Status is the HTTP status code, or "error" if no response was received.
+pub static UPSTREAM_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+  register_int_counter_vec!(
+    "media_resolver_upstream_requests_total",
+    "Number of upstream requests by upstream and response status, including retries.",
+    &["upstream", "status"]
+  )
+  .unwrap()
+});
+
+pub static UPSTREAM_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+  register_histogram_vec!(
+    "media_resolver_upstream_duration_seconds",
+    "Time taken by each upstream request by upstream.",
+    &["upstream"]
+  )
+  .unwrap()
+});
+
+pub static CACHE: LazyLock<IntCounterVec> = LazyLock::new(|| {
+  register_int_counter_vec!(
+    "media_resolver_cache_total",
+    "Number of cache lookups by result (hit, miss or coalesced).",
+    &["result"]
+  )
+  .unwrap()
+});
+
+pub static RESPONSES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+  register_int_counter_vec!(
+    "media_resolver_responses_total",
+    "Number of successful /resolve responses by output format.",
+    &["output"]
+  )
+  .unwrap()
+});
+
+pub static CLIENT_RATE_LIMITED: LazyLock<IntCounter> = LazyLock::new(|| {
+  register_int_counter!(
+    "media_resolver_client_rate_limited_total",
+    "Number of requests rejected by the per-client rate limit."
+  )
+  .unwrap()
+});
+
+// Version is the v parameter sent by twitch.lua, "none" if it's missing or "other" if it's invalid or too large
+pub static CLIENT_VERSIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+  register_int_counter_vec!(
+    "media_resolver_client_versions_total",
+    "Number of /resolve requests by client version.",
+    &["version"]
+  )
+  .unwrap()
+});
+
+pub fn render() -> String {
+  let mut buffer = vec![];
+  TextEncoder::new()
+    .encode(&prometheus::gather(), &mut buffer)
+    .unwrap();
+  return String::from_utf8(buffer).unwrap();
+}
@@ -0,0 +1,386 @@
+pub mod asx;
+pub mod atom;
+mod feed;
+pub mod html;
+pub mod json;
+pub mod jsonfeed;
+pub mod m3u;
+pub mod pls;
+pub mod rss;
+pub mod xspf;
+
+use actix_web::{
+  http::header::{HeaderName, HeaderValue},
+  HttpResponse,
+};
+
+use crate::{ItemKind, PlaylistItem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+  // Redirect to the first item in the playlist (the default)
+  Redirect,
+  Json,
+  M3u,
+  Xspf,
+  Pls,
+  Asx,
+  Rss,
+  JsonFeed,
+  Atom,
+  Html,
+}
+
+// Information about the request that some outputs need
+pub struct Context<'a> {
+  // The URL that was resolved
+  pub url: &'a str,
+  // The public URL of this server, without a trailing slash
+  pub base_url: String,
+  // The version of the JSON output, from the v query parameter
+  pub version: u8,
+  // The title and description of the listing, if the URL is one
+  pub title: Option<&'a str>,
+  pub description: Option<&'a str>,
+}
+
+impl Context<'_> {
+  // Links back to /resolve so that the URL is resolved when it is used
+  pub fn resolve_url(&self, url: &str, output: Option<Output>) -> String {
+    let mut resolve_url = format!("{}/resolve?url={}", self.base_url, urlencoding::encode(url));
+    if let Some(output) = output {
+      resolve_url.push_str("&output=");
+      resolve_url.push_str(output.name());
+    }
+    return resolve_url;
+  }
+
+  // Items in listings are pages on twitch.tv that players can't play, so they link back to /resolve instead.
+  // "Load more" gets the next page in the same format.
+  pub fn item_url(&self, item: &PlaylistItem, output: Output) -> String {
+    if item.kind == ItemKind::Pagination {
+      return self.resolve_url(&item.path, Some(output));
+    }
+    if item.id.as_deref() == Some(item.path.as_str()) {
+      return self.resolve_url(&item.path, None);
+    }
+    return item.path.clone();
+  }
+}
+
+#[cfg(test)]
+impl<'a> Context<'a> {
+  // Like PlaylistItem::test, tests set the fields they care about with ..Context::test(..)
+  pub fn test(url: &'a str, title: Option<&'a str>) -> Context<'a> {
+    Context {
+      url,
+      base_url: String::from("http://localhost:8080"),
+      version: 1,
+      title,
+      description: None,
+    }
+  }
+}
+
+impl Output {
+  pub fn from_param(output: Option<&str>) -> Output {
+    match output {
+      Some("json") => Output::Json,
+      Some("m3u") | Some("m3u8") => Output::M3u,
+      Some("xspf") => Output::Xspf,
+      Some("pls") => Output::Pls,
+      Some("asx") => Output::Asx,
+      Some("rss") => Output::Rss,
+      Some("jsonfeed") => Output::JsonFeed,
+      Some("atom") => Output::Atom,
+      Some("html") => Output::Html,
+      _ => Output::Redirect,
+    }
+  }
+
+  // The output query parameter takes precedence over the Accept header
+  pub fn negotiate(output: Option<&str>, accept: Option<&str>) -> Output {
+    if output.is_some() {
+      return Output::from_param(output);
+    }
+    return accept.map(Output::from_accept).unwrap_or(Output::Redirect);
+  }
+
+  // Picks the media type with the highest quality that we can produce, ties go to the first one listed
+  fn from_accept(accept: &str) -> Output {
+    let mut best: Option<(Output, f32)> = None;
+    for media_range in accept.split(',') {
+      let mut params = media_range.split(';');
+      let media_type = params
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+      let quality = params
+        .filter_map(|param| param.trim().strip_prefix("q="))
+        .find_map(|q| q.trim().parse::<f32>().ok())
+        .unwrap_or(1.0);
+      let output = match media_type.as_str() {
+        "application/json" => Output::Json,
+        "audio/x-mpegurl" | "audio/mpegurl" | "application/vnd.apple.mpegurl" => Output::M3u,
+        "application/xspf+xml" => Output::Xspf,
+        "audio/x-scpls" => Output::Pls,
+        "video/x-ms-asf" => Output::Asx,
+        "application/rss+xml" => Output::Rss,
+        "application/feed+json" => Output::JsonFeed,
+        "application/atom+xml" => Output::Atom,
+        "text/html" => Output::Html,
+        _ => continue,
+      };
+      if quality > 0.0 && best.map_or(true, |(_, q)| quality > q) {
+        best = Some((output, quality));
+      }
+    }
+    return best.map(|(output, _)| output).unwrap_or(Output::Redirect);
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Output::Redirect => "redirect",
+      Output::Json => "json",
+      Output::M3u => "m3u",
+      Output::Xspf => "xspf",
+      Output::Pls => "pls",
+      Output::Asx => "asx",
+      Output::Rss => "rss",
+      Output::JsonFeed => "jsonfeed",
+      Output::Atom => "atom",
+      Output::Html => "html",
+    }
+  }
+
+  // Playlists can contain notices for the user, e.g. to upgrade twitch.lua
+  pub fn is_playlist(&self) -> bool {
+    matches!(
+      self,
+      Output::Json | Output::M3u | Output::Xspf | Output::Pls | Output::Asx
+    )
+  }
+
+  // Feeds are only useful for listings, e.g. the videos of a channel
+  pub fn is_feed(&self) -> bool {
+    matches!(self, Output::Rss | Output::JsonFeed | Output::Atom)
+  }
+}
+
+pub fn render(output: Output, playlist: &[PlaylistItem], ctx: &Context) -> HttpResponse {
+  match output {
+    Output::Redirect => match playlist.first() {
+      Some(item) => HttpResponse::TemporaryRedirect()
+        .append_header(("Location", item.path.as_str()))
+        .finish(),
+      None => HttpResponse::NotFound().finish(),
+    },
+    Output::Json if ctx.version >= 2 => HttpResponse::Ok().json(json::v2(playlist)),
+    Output::Json => HttpResponse::Ok().json(playlist),
+    Output::M3u => playlist_file(
+      "audio/x-mpegurl; charset=utf-8",
+      "m3u",
+      m3u::render(playlist, ctx),
+      filename(playlist, ctx.title),
+    ),
+    Output::Xspf => playlist_file(
+      "application/xspf+xml; charset=utf-8",
+      "xspf",
+      xspf::render(playlist, ctx),
+      filename(playlist, ctx.title),
+    ),
+    Output::Pls => playlist_file(
+      "audio/x-scpls; charset=utf-8",
+      "pls",
+      pls::render(playlist, ctx),
+      filename(playlist, ctx.title),
+    ),
+    Output::Asx => playlist_file(
+      "video/x-ms-asf; charset=utf-8",
+      "asx",
+      asx::render(playlist, ctx),
+      filename(playlist, ctx.title),
+    ),
+    Output::Rss => HttpResponse::Ok()
+      .content_type("application/rss+xml; charset=utf-8")
+      .body(rss::render(playlist, ctx)),
+    Output::JsonFeed => HttpResponse::Ok()
+      .content_type("application/feed+json; charset=utf-8")
+      .body(jsonfeed::render(playlist, ctx).to_string()),
+    Output::Atom => HttpResponse::Ok()
+      .content_type("application/atom+xml; charset=utf-8")
+      .body(atom::render(playlist, ctx)),
+    Output::Html => HttpResponse::Ok()
+      .content_type("text/html; charset=utf-8")
+      .body(html::render(playlist, ctx)),
+  }
+}
+
+fn playlist_file(
+  content_type: &str,
+  extension: &str,
+  body: String,
+  filename: String,
+) -> HttpResponse {
+  return HttpResponse::Ok()
+    .content_type(content_type)
+    .append_header((
+      "Content-Disposition",
+      format!(
+        "inline; filename=\"{}.{}\"; filename*=UTF-8''{}.{}",
+        filename
+          .chars()
+          .map(|c| if c.is_ascii() { c } else { '_' })
+          .collect::<String>(),
+        extension,
+        urlencoding::encode(&filename),
+        extension,
+      ),
+    ))
+    .body(body);
+}
+
+// Listings are named after their title, a single item after its own title
+fn filename(playlist: &[PlaylistItem], title: Option<&str>) -> String {
+  let name = match playlist {
+    _ if title.is_some() => title,
+    [item] => Some(item.name.as_str()),
+    [item, ..] => item.artist.as_deref().or(Some(item.name.as_str())),
+    [] => None,
+  };
+  let filename: String = name
+    .unwrap_or_default()
+    .chars()
+    .map(|c| {
+      if c.is_control() || "/\\:*?\"<>|".contains(c) {
+        '_'
+      } else {
+        c
+      }
+    })
+    .take(100)
+    .collect();
+  let filename = filename.trim();
+  if filename.is_empty() {
+    return String::from("playlist");
+  }
+  return filename.to_string();
+}
+
+pub fn insert_header(response: &mut HttpResponse, name: &'static str, value: &str) {
+  if let Ok(value) = HeaderValue::from_str(value) {
+    response
+      .headers_mut()
+      .insert(HeaderName::from_static(name), value);
+  }
+}
+
+// Formats a duration in seconds like "1:02:03", or "2:03" if it's shorter than an hour
+pub fn format_duration(duration: usize) -> String {
+  let (hours, minutes, seconds) = (duration / 3600, duration / 60 % 60, duration % 60);
+  if hours > 0 {
+    return format!("{}:{:02}:{:02}", hours, minutes, seconds);
+  }
+  return format!("{}:{:02}", minutes, seconds);
+}
+
+// Joins multi-line values for formats where every value has to fit on a single line
+pub fn single_line(s: &str) -> String {
+  return s
+    .split(['\r', '\n'])
+    .filter(|line| !line.trim().is_empty())
+    .collect::<Vec<_>>()
+    .join(" ");
+}
+
+pub fn xml_escape(s: &str) -> String {
+  let mut escaped = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      // Control characters are not allowed in XML 1.0
+      c if c.is_control() && c != '\n' && c != '\r' && c != '\t' => {}
+      c => escaped.push(c),
+    }
+  }
+  return escaped;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn item(name: &str, artist: Option<&str>) -> PlaylistItem {
+    PlaylistItem {
+      artist: artist.map(String::from),
+      ..PlaylistItem::test(
+        ItemKind::Vod,
+        "https://www.twitch.tv/videos/113837699",
+        name,
+      )
+    }
+  }
+
+  #[test]
+  fn test() {
+    assert_eq!(filename(&[], None), "playlist");
+    assert_eq!(
+      filename(
+        &[item("AGDQ 2017: Mickey's \"Dangerous\" Chase", None)],
+        None
+      ),
+      "AGDQ 2017_ Mickey's _Dangerous_ Chase"
+    );
+    assert_eq!(
+      filename(
+        &[
+          item("Video 1", Some("GamesDoneQuick")),
+          item("Video 2", Some("GamesDoneQuick"))
+        ],
+        None
+      ),
+      "GamesDoneQuick"
+    );
+    assert_eq!(
+      filename(
+        &[
+          item("Video 1", Some("GamesDoneQuick")),
+          item("Video 2", Some("SpeedGaming"))
+        ],
+        Some("Super Mario 64")
+      ),
+      "Super Mario 64"
+    );
+    assert_eq!(
+      Output::negotiate(None, Some("application/json")),
+      Output::Json
+    );
+    assert_eq!(
+      Output::negotiate(Some("m3u"), Some("application/json")),
+      Output::M3u
+    );
+    assert_eq!(
+      Output::negotiate(
+        None,
+        Some("text/plain, application/rss+xml;q=0.5, text/html;q=0.9")
+      ),
+      Output::Html
+    );
+    assert_eq!(
+      Output::negotiate(None, Some("application/json;q=0, */*")),
+      Output::Redirect
+    );
+    assert_eq!(Output::negotiate(None, None), Output::Redirect);
+    assert_eq!(format_duration(59), "0:59");
+    assert_eq!(format_duration(3723), "1:02:03");
+    assert_eq!(
+      xml_escape("<a href=\"x\">Tom & Jerry's</a>"),
+      "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+    );
+  }
+}
@@ -0,0 +1,86 @@
+use super::{xml_escape, Context, Output};
+use crate::PlaylistItem;
+
+// https://learn.microsoft.com/en-us/windows/win32/wmp/asx-element
+pub fn render(playlist: &[PlaylistItem], ctx: &Context) -> String {
+  let mut asx = String::from("<asx version=\"3.0\">\n");
+  for item in playlist.iter() {
+    asx.push_str("  <entry>\n");
+    asx.push_str(&format!("    <title>{}</title>\n", xml_escape(&item.name)));
+    if let Some(artist) = item.artist.as_ref() {
+      asx.push_str(&format!("    <author>{}</author>\n", xml_escape(artist)));
+    }
+    if let Some(description) = item.description.as_ref() {
+      asx.push_str(&format!(
+        "    <abstract>{}</abstract>\n",
+        xml_escape(description)
+      ));
+    }
+    if let Some(duration) = item.duration {
+      asx.push_str(&format!(
+        "    <duration value=\"{:02}:{:02}:{:02}\" />\n",
+        duration / 3600,
+        duration / 60 % 60,
+        duration % 60
+      ));
+    }
+    asx.push_str(&format!(
+      "    <ref href=\"{}\" />\n",
+      xml_escape(&ctx.item_url(item, Output::Asx))
+    ));
+    asx.push_str("  </entry>\n");
+  }
+  asx.push_str("</asx>\n");
+  return asx;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ItemKind;
+
+  #[test]
+  fn test() {
+    let playlist = vec![
+      PlaylistItem {
+        artist: Some(String::from("Tom & Jerry")),
+        description: Some(String::from("<b>Chase</b>")),
+        duration: Some(3723),
+        ..PlaylistItem::test(
+          ItemKind::Clip,
+          "https://production.assets.clips.twitchcdn.net/clip.mp4?sig=abc&token=def",
+          "Über Clip",
+        )
+      },
+      PlaylistItem::test(
+        ItemKind::Live,
+        "https://usher.ttvnw.net/api/channel/hls/speedgaming.m3u8",
+        "Speedgaming",
+      ),
+    ];
+    let ctx = Context::test("https://clips.twitch.tv/UberClip", None);
+    assert_eq!(
+      render(&playlist, &ctx),
+      r#"<asx version="3.0">
+  <entry>
+    <title>Über Clip</title>
+    <author>Tom &amp; Jerry</author>
+    <abstract>&lt;b&gt;Chase&lt;/b&gt;</abstract>
+    <duration value="01:02:03" />
+    <ref href="https://production.assets.clips.twitchcdn.net/clip.mp4?sig=abc&amp;token=def" />
+  </entry>
+  <entry>
+    <title>Speedgaming</title>
+    <ref href="https://usher.ttvnw.net/api/channel/hls/speedgaming.m3u8" />
+  </entry>
+</asx>
+"#
+    );
+
+    let response = super::super::render(Output::Asx, &playlist[..1], &ctx);
+    assert_eq!(
+      response.headers().get("content-disposition").unwrap(),
+      "inline; filename=\"_ber Clip.asx\"; filename*=UTF-8''%C3%9Cber%20Clip.asx"
+    );
+  }
+}
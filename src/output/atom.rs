@@ -0,0 +1,171 @@
+use super::feed::{item_id, media_type, rfc3339, Feed};
+use super::{xml_escape, Context, Output};
+use crate::PlaylistItem;
+
+// https://datatracker.ietf.org/doc/html/rfc4287
+pub fn render(playlist: &[PlaylistItem], ctx: &Context) -> String {
+  let feed = Feed::new(playlist, ctx, Output::Atom);
+  let updated = feed.updated();
+
+  let mut atom = String::from(
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+"#,
+  );
+  atom.push_str(&element(1, "id", feed.home_page_url));
+  atom.push_str(&element(1, "title", feed.title));
+  atom.push_str(&element(1, "subtitle", feed.description));
+  atom.push_str(&element(1, "updated", &rfc3339(&updated)));
+  atom.push_str(&link(1, "alternate", feed.home_page_url, None));
+  atom.push_str(&link(
+    1,
+    "self",
+    &feed.feed_url,
+    Some("application/atom+xml"),
+  ));
+  if let Some(next_url) = feed.next_url.as_ref() {
+    atom.push_str(&link(1, "next", next_url, Some("application/atom+xml")));
+  }
+  // Required unless every entry has an author, which the items of a category often don't
+  atom.push_str(&format!(
+    "  <author>\n    <name>{}</name>\n  </author>\n",
+    xml_escape(feed.author.unwrap_or(feed.title))
+  ));
+
+  for item in feed.items.iter() {
+    atom.push_str("  <entry>\n");
+    atom.push_str(&element(2, "id", item_id(item)));
+    atom.push_str(&element(2, "title", &item.name));
+    atom.push_str(&link(2, "alternate", &item.path, None));
+    atom.push_str(&link(
+      2,
+      "enclosure",
+      &ctx.item_url(item, Output::Atom),
+      Some(media_type(item)),
+    ));
+    if let Some(date) = item.date.as_ref() {
+      atom.push_str(&element(2, "published", &rfc3339(date)));
+    }
+    atom.push_str(&element(
+      2,
+      "updated",
+      &rfc3339(item.date.as_ref().unwrap_or(&updated)),
+    ));
+    if let Some(artist) = item.artist.as_ref() {
+      atom.push_str(&format!(
+        "    <author>\n      <name>{}</name>\n    </author>\n",
+        xml_escape(artist)
+      ));
+    }
+    if let Some(genre) = item.genre.as_ref() {
+      atom.push_str(&format!(
+        "    <category term=\"{}\" />\n",
+        xml_escape(genre)
+      ));
+    }
+    if let Some(description) = item.description.as_ref() {
+      atom.push_str(&element(2, "summary", description));
+    }
+    atom.push_str("  </entry>\n");
+  }
+
+  atom.push_str("</feed>\n");
+  return atom;
+}
+
+fn element(depth: usize, name: &str, value: &str) -> String {
+  return format!(
+    "{}<{}>{}</{}>\n",
+    "  ".repeat(depth),
+    name,
+    xml_escape(value),
+    name
+  );
+}
+
+fn link(depth: usize, rel: &str, href: &str, content_type: Option<&str>) -> String {
+  let content_type = content_type
+    .map(|t| format!(" type=\"{}\"", t))
+    .unwrap_or_default();
+  return format!(
+    "{}<link rel=\"{}\" href=\"{}\"{} />\n",
+    "  ".repeat(depth),
+    rel,
+    xml_escape(href),
+    content_type
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ItemKind;
+
+  #[test]
+  fn test() {
+    let playlist = vec![
+      PlaylistItem {
+        id: Some(String::from("https://www.twitch.tv/videos/113837699")),
+        artist: Some(String::from("GamesDoneQuick")),
+        genre: Some(String::from("System Shock 2")),
+        date: Some("2017-01-10T20:10:16Z".parse().unwrap()),
+        description: Some(String::from("Tom & Jerry")),
+        ..PlaylistItem::test(
+          ItemKind::Vod,
+          "https://www.twitch.tv/videos/113837699",
+          "AGDQ 2017 - Mickey's Dangerous Chase",
+        )
+      },
+      PlaylistItem {
+        id: Some(String::from("https://www.twitch.tv/speedgaming")),
+        ..PlaylistItem::test(ItemKind::Live, "https://www.twitch.tv/speedgaming", "Live")
+      },
+      PlaylistItem::test(
+        ItemKind::Pagination,
+        "https://www.twitch.tv/gamesdonequick/videos?cursor=abc",
+        "Load more",
+      ),
+    ];
+    let ctx = Context::test(
+      "https://www.twitch.tv/gamesdonequick/videos",
+      Some("GamesDoneQuick"),
+    );
+    assert_eq!(
+      render(&playlist, &ctx),
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>https://www.twitch.tv/gamesdonequick/videos</id>
+  <title>GamesDoneQuick</title>
+  <subtitle>https://www.twitch.tv/gamesdonequick/videos</subtitle>
+  <updated>2017-01-10T20:10:16Z</updated>
+  <link rel="alternate" href="https://www.twitch.tv/gamesdonequick/videos" />
+  <link rel="self" href="http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fgamesdonequick%2Fvideos&amp;output=atom" type="application/atom+xml" />
+  <link rel="next" href="http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fgamesdonequick%2Fvideos%3Fcursor%3Dabc&amp;output=atom" type="application/atom+xml" />
+  <author>
+    <name>GamesDoneQuick</name>
+  </author>
+  <entry>
+    <id>https://www.twitch.tv/videos/113837699</id>
+    <title>AGDQ 2017 - Mickey&apos;s Dangerous Chase</title>
+    <link rel="alternate" href="https://www.twitch.tv/videos/113837699" />
+    <link rel="enclosure" href="http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fvideos%2F113837699" type="application/x-mpegURL" />
+    <published>2017-01-10T20:10:16Z</published>
+    <updated>2017-01-10T20:10:16Z</updated>
+    <author>
+      <name>GamesDoneQuick</name>
+    </author>
+    <category term="System Shock 2" />
+    <summary>Tom &amp; Jerry</summary>
+  </entry>
+  <entry>
+    <id>https://www.twitch.tv/speedgaming</id>
+    <title>Live</title>
+    <link rel="alternate" href="https://www.twitch.tv/speedgaming" />
+    <link rel="enclosure" href="http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fspeedgaming" type="application/x-mpegURL" />
+    <updated>2017-01-10T20:10:16Z</updated>
+  </entry>
+</feed>
+"#
+    );
+  }
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use super::{Context, Output};
+use crate::{ItemKind, PlaylistItem};
+
+// The parts that the RSS, Atom and JSON Feed outputs have in common
+pub struct Feed<'a> {
+  pub title: &'a str,
+  pub description: &'a str,
+  pub author: Option<&'a str>,
+  pub language: Option<&'a str>,
+  pub home_page_url: &'a str,
+  pub feed_url: String,
+  pub next_url: Option<String>,
+  pub items: Vec<&'a PlaylistItem>,
+}
+
+impl<'a> Feed<'a> {
+  pub fn new(playlist: &'a [PlaylistItem], ctx: &'a Context, output: Output) -> Feed<'a> {
+    let items: Vec<_> = playlist
+      .iter()
+      .filter(|item| item.kind != ItemKind::Pagination)
+      .collect();
+    let author = shared(&items, |item| item.artist.as_deref());
+    Feed {
+      title: ctx.title.or(author).unwrap_or(ctx.url),
+      description: ctx.description.unwrap_or(ctx.url),
+      author,
+      language: shared(&items, |item| item.language.as_deref()),
+      home_page_url: ctx.url,
+      feed_url: ctx.resolve_url(ctx.url, Some(output)),
+      next_url: playlist
+        .iter()
+        .find(|item| item.kind == ItemKind::Pagination)
+        .map(|item| ctx.resolve_url(&item.path, Some(output))),
+      items,
+    }
+  }
+
+  // The date of the newest item, or now if no item has a date
+  pub fn updated(&self) -> DateTime<Utc> {
+    return self
+      .items
+      .iter()
+      .filter_map(|item| item.date)
+      .max()
+      .unwrap_or_else(|| DateTime::from(std::time::SystemTime::now()));
+  }
+}
+
+// The value that all items have in common, e.g. the channel of a list of videos. The streams in a category are
+// from different channels, so they have no author.
+fn shared<'a>(
+  items: &[&'a PlaylistItem],
+  value: impl Fn(&'a PlaylistItem) -> Option<&'a str>,
+) -> Option<&'a str> {
+  let first = value(items.first()?)?;
+  if items.iter().all(|item| value(item) == Some(first)) {
+    return Some(first);
+  }
+  return None;
+}
+
+// The type of what the enclosure links to. /resolve redirects clips to an MP4 file and everything else to HLS.
+pub fn media_type(item: &PlaylistItem) -> &'static str {
+  match item.kind {
+    ItemKind::Clip => "video/mp4",
+    _ => "application/x-mpegURL",
+  }
+}
+
+// Falls back to the path for items without a stable id
+pub fn item_id(item: &PlaylistItem) -> &str {
+  return item.id.as_deref().unwrap_or(&item.path);
+}
+
+pub fn rfc3339(date: &DateTime<Utc>) -> String {
+  return date.to_rfc3339_opts(SecondsFormat::Secs, true);
+}
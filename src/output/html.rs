@@ -0,0 +1,141 @@
+use super::{format_duration, xml_escape as escape, Context, Output};
+use crate::{format_date, ItemKind, PlaylistItem};
+
+// A simple page for looking at what a URL resolves to in a browser
+pub fn render(playlist: &[PlaylistItem], ctx: &Context) -> String {
+  let title = match (ctx.title, playlist) {
+    (Some(title), _) => title,
+    (None, [item]) => item.name.as_str(),
+    (None, _) => ctx.url,
+  };
+
+  let mut html = format!(
+    r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>{}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 60em; margin: 0 auto; padding: 1em; }}
+article {{ border-bottom: 1px solid #ccc; padding: 1em 0; }}
+dt {{ font-weight: bold; }}
+input {{ width: 100%; font-family: monospace; }}
+video {{ max-width: 100%; }}
+</style>
+</head>
+<body>
+<h1>{}</h1>
+<p>Resolved from <a href="{}">{}</a></p>
+"#,
+    escape(title),
+    escape(title),
+    escape(ctx.url),
+    escape(ctx.url),
+  );
+
+  for item in playlist.iter() {
+    if item.kind == ItemKind::Pagination {
+      html.push_str(&format!(
+        "<nav><a href=\"{}\">{}</a></nav>\n",
+        escape(&ctx.resolve_url(&item.path, Some(Output::Html))),
+        escape(&item.name)
+      ));
+      continue;
+    }
+
+    html.push_str("<article>\n");
+    html.push_str(&format!("<h2>{}</h2>\n", escape(&item.name)));
+    html.push_str("<dl>\n");
+    if let Some(artist) = item.artist.as_ref() {
+      html.push_str(&field("Channel", artist));
+    }
+    if let Some(genre) = item.genre.as_ref() {
+      html.push_str(&field("Game", genre));
+    }
+    if let Some(date) = item.date.as_ref() {
+      html.push_str(&field("Date", &format_date(date)));
+    }
+    if let Some(duration) = item.duration {
+      html.push_str(&field("Duration", &format_duration(duration)));
+    }
+    if let Some(start_time) = item.start_time {
+      html.push_str(&field("Starts at", &format_duration(start_time)));
+    }
+    if let Some(language) = item.language.as_ref() {
+      html.push_str(&field("Language", language));
+    }
+    html.push_str("</dl>\n");
+    if let Some(description) = item.description.as_ref() {
+      html.push_str(&format!("<p>{}</p>\n", escape(description)));
+    }
+
+    if item.id.as_deref() == Some(item.path.as_str()) {
+      // Listings link to the video and clip pages, which are resolved when they are followed
+      html.push_str(&format!(
+        "<p><a href=\"{}\">Resolve</a></p>\n",
+        escape(&ctx.resolve_url(&item.path, Some(Output::Html)))
+      ));
+    } else {
+      // Browsers without native HLS support can't play the video, but the URL can still be copied
+      html.push_str(&format!(
+        "<input type=\"text\" readonly value=\"{}\" onclick=\"this.select()\">\n",
+        escape(&item.path)
+      ));
+      html.push_str(&format!(
+        "<video controls preload=\"none\" src=\"{}\"></video>\n",
+        escape(&item.path)
+      ));
+      html.push_str(&format!(
+        "<p><a href=\"{}\">Open media URL</a></p>\n",
+        escape(&item.path)
+      ));
+    }
+    html.push_str("</article>\n");
+  }
+
+  html.push_str("</body>\n</html>\n");
+  return html;
+}
+
+fn field(name: &str, value: &str) -> String {
+  return format!("<dt>{}</dt><dd>{}</dd>\n", name, escape(value));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test() {
+    let playlist = vec![
+      PlaylistItem {
+        artist: Some(String::from("GamesDoneQuick")),
+        genre: Some(String::from("Super Mario 64")),
+        duration: Some(3723),
+        id: Some(String::from("https://www.twitch.tv/videos/113837699")),
+        ..PlaylistItem::test(
+          ItemKind::Vod,
+          "https://www.twitch.tv/videos/113837699",
+          "<AGDQ 2017>",
+        )
+      },
+      PlaylistItem::test(
+        ItemKind::Pagination,
+        "https://www.twitch.tv/gamesdonequick/videos?cursor=abc",
+        "Load more",
+      ),
+    ];
+    let ctx = Context::test(
+      "https://www.twitch.tv/gamesdonequick/videos",
+      Some("GamesDoneQuick"),
+    );
+    let html = render(&playlist, &ctx);
+    assert!(html.contains("<title>GamesDoneQuick</title>"));
+    assert!(html.contains("<h2>&lt;AGDQ 2017&gt;</h2>"));
+    assert!(html.contains("<dt>Duration</dt><dd>1:02:03</dd>"));
+    assert!(html.contains("<a href=\"http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fvideos%2F113837699&amp;output=html\">Resolve</a>"));
+    assert!(html.contains("<nav><a href=\"http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fgamesdonequick%2Fvideos%3Fcursor%3Dabc&amp;output=html\">Load more</a></nav>"));
+  }
+}
@@ -0,0 +1,97 @@
+use serde::Serialize;
+
+use super::feed::rfc3339;
+use super::format_duration;
+use crate::hls::Variant;
+use crate::{ItemKind, PlaylistItem};
+
+// Version 1 is the PlaylistItem itself, which old twitch.lua scripts depend on, so it must not change.
+// Version 2 is requested with v=2.
+#[derive(Debug, Serialize)]
+pub struct Playlist<'a> {
+  version: u8,
+  items: Vec<Item<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Item<'a> {
+  kind: ItemKind,
+  path: &'a str,
+  name: &'a str,
+  description: Option<&'a str>,
+  language: Option<&'a str>,
+  artist: Option<&'a str>,
+  genre: Option<&'a str>,
+  date: Option<String>, // RFC 3339, e.g. "2017-01-10T20:10:16Z"
+  duration: Option<Duration>,
+  start_time: Option<Duration>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  variants: Option<&'a [Variant]>,
+}
+
+#[derive(Debug, Serialize)]
+struct Duration {
+  seconds: usize,
+  human: String, // e.g. "1:02:03"
+}
+
+pub fn v2(playlist: &[PlaylistItem]) -> Playlist<'_> {
+  Playlist {
+    version: 2,
+    items: playlist
+      .iter()
+      .map(|item| Item {
+        kind: item.kind,
+        path: &item.path,
+        name: &item.name,
+        description: item.description.as_deref(),
+        language: item.language.as_deref(),
+        artist: item.artist.as_deref(),
+        genre: item.genre.as_deref(),
+        date: item.date.as_ref().map(rfc3339),
+        duration: item.duration.map(|seconds| Duration {
+          seconds,
+          human: format_duration(seconds),
+        }),
+        start_time: item.start_time.map(|seconds| Duration {
+          seconds,
+          human: format_duration(seconds),
+        }),
+        variants: item.variants.as_deref(),
+      })
+      .collect(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::DateTime;
+
+  #[test]
+  fn test() {
+    let playlist = vec![PlaylistItem {
+      language: Some(String::from("en")),
+      artist: Some(String::from("GamesDoneQuick")),
+      date: DateTime::parse_from_rfc3339("2017-01-10T20:10:16Z")
+        .ok()
+        .map(|d| d.to_utc()),
+      duration: Some(3723),
+      ..PlaylistItem::test(
+        ItemKind::Vod,
+        "https://usher.ttvnw.net/vod/113837699.m3u8",
+        "AGDQ 2017",
+      )
+    }];
+
+    // v1 stays the way it has always been
+    assert_eq!(
+      serde_json::to_string(&playlist).unwrap(),
+      r#"[{"path":"https://usher.ttvnw.net/vod/113837699.m3u8","name":"AGDQ 2017","description":null,"language":"en","artist":"GamesDoneQuick","genre":null,"date":"2017-01-10 20:10:16","duration":3723}]"#
+    );
+    assert_eq!(
+      serde_json::to_string(&v2(&playlist)).unwrap(),
+      r#"{"version":2,"items":[{"kind":"vod","path":"https://usher.ttvnw.net/vod/113837699.m3u8","name":"AGDQ 2017","description":null,"language":"en","artist":"GamesDoneQuick","genre":null,"date":"2017-01-10T20:10:16Z","duration":{"seconds":3723,"human":"1:02:03"},"start_time":null}]}"#
+    );
+  }
+}
@@ -0,0 +1,152 @@
+use serde_json::{json, Map, Value};
+
+use super::feed::{item_id, media_type, rfc3339, Feed};
+use super::{Context, Output};
+use crate::PlaylistItem;
+
+// https://www.jsonfeed.org/version/1.1/
+pub fn render(playlist: &[PlaylistItem], ctx: &Context) -> Value {
+  let feed = Feed::new(playlist, ctx, Output::JsonFeed);
+
+  let items: Vec<_> = feed
+    .items
+    .iter()
+    .map(|item| {
+      let mut attachment = json!({
+        "url": ctx.item_url(item, Output::JsonFeed),
+        "mime_type": media_type(item),
+        "title": item.name,
+      });
+      if let Some(duration) = item.duration {
+        attachment["duration_in_seconds"] = json!(duration);
+      }
+
+      let mut v = Map::new();
+      v.insert(String::from("id"), json!(item_id(item)));
+      v.insert(String::from("url"), json!(item.path));
+      v.insert(String::from("title"), json!(item.name));
+      v.insert(
+        String::from("content_text"),
+        json!(item.description.as_ref().unwrap_or(&item.name)),
+      );
+      if let Some(date) = item.date.as_ref() {
+        v.insert(String::from("date_published"), json!(rfc3339(date)));
+      }
+      if let Some(artist) = item.artist.as_ref() {
+        v.insert(String::from("authors"), json!([{ "name": artist }]));
+      }
+      if let Some(genre) = item.genre.as_ref() {
+        v.insert(String::from("tags"), json!([genre]));
+      }
+      if let Some(language) = item.language.as_ref() {
+        v.insert(String::from("language"), json!(language));
+      }
+      v.insert(String::from("attachments"), json!([attachment]));
+      return Value::Object(v);
+    })
+    .collect();
+
+  let mut v = Map::new();
+  v.insert(
+    String::from("version"),
+    json!("https://jsonfeed.org/version/1.1"),
+  );
+  v.insert(String::from("title"), json!(feed.title));
+  v.insert(String::from("home_page_url"), json!(feed.home_page_url));
+  v.insert(String::from("feed_url"), json!(feed.feed_url));
+  v.insert(String::from("description"), json!(feed.description));
+  if let Some(next_url) = feed.next_url.as_ref() {
+    v.insert(String::from("next_url"), json!(next_url));
+  }
+  if let Some(author) = feed.author {
+    v.insert(String::from("authors"), json!([{ "name": author }]));
+  }
+  if let Some(language) = feed.language {
+    v.insert(String::from("language"), json!(language));
+  }
+  v.insert(String::from("items"), json!(items));
+  return Value::Object(v);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ItemKind;
+
+  #[test]
+  fn test() {
+    let playlist = vec![
+      PlaylistItem {
+        id: Some(String::from("https://www.twitch.tv/videos/113837699")),
+        language: Some(String::from("en")),
+        artist: Some(String::from("GamesDoneQuick")),
+        genre: Some(String::from("System Shock 2")),
+        date: Some("2017-01-10T20:10:16Z".parse().unwrap()),
+        duration: Some(118070),
+        ..PlaylistItem::test(
+          ItemKind::Vod,
+          "https://www.twitch.tv/videos/113837699",
+          "AGDQ 2017 - Mickey's Dangerous Chase",
+        )
+      },
+      PlaylistItem {
+        id: Some(String::from("https://www.twitch.tv/speedgaming")),
+        description: Some(String::from("Speedrunning")),
+        ..PlaylistItem::test(
+          ItemKind::Live,
+          "https://www.twitch.tv/speedgaming",
+          "Speedgaming",
+        )
+      },
+      PlaylistItem::test(
+        ItemKind::Pagination,
+        "https://www.twitch.tv/gamesdonequick/videos?cursor=abc",
+        "Load more",
+      ),
+    ];
+    let ctx = Context::test(
+      "https://www.twitch.tv/gamesdonequick/videos",
+      Some("GamesDoneQuick"),
+    );
+    assert_eq!(
+      render(&playlist, &ctx),
+      json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": "GamesDoneQuick",
+        "home_page_url": "https://www.twitch.tv/gamesdonequick/videos",
+        "feed_url": "http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fgamesdonequick%2Fvideos&output=jsonfeed",
+        "description": "https://www.twitch.tv/gamesdonequick/videos",
+        "next_url": "http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fgamesdonequick%2Fvideos%3Fcursor%3Dabc&output=jsonfeed",
+        "items": [
+          {
+            "id": "https://www.twitch.tv/videos/113837699",
+            "url": "https://www.twitch.tv/videos/113837699",
+            "title": "AGDQ 2017 - Mickey's Dangerous Chase",
+            "content_text": "AGDQ 2017 - Mickey's Dangerous Chase",
+            "date_published": "2017-01-10T20:10:16Z",
+            "authors": [{ "name": "GamesDoneQuick" }],
+            "tags": ["System Shock 2"],
+            "language": "en",
+            "attachments": [{
+              "url": "http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fvideos%2F113837699",
+              "mime_type": "application/x-mpegURL",
+              "title": "AGDQ 2017 - Mickey's Dangerous Chase",
+              "duration_in_seconds": 118070,
+            }],
+          },
+          {
+            "id": "https://www.twitch.tv/speedgaming",
+            "url": "https://www.twitch.tv/speedgaming",
+            "title": "Speedgaming",
+            "content_text": "Speedrunning",
+            "attachments": [{
+              "url": "http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fspeedgaming",
+              "mime_type": "application/x-mpegURL",
+              "title": "Speedgaming",
+            }],
+          },
+        ],
+      })
+    );
+  }
+}
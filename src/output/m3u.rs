@@ -0,0 +1,118 @@
+use super::{single_line, Context, Output};
+use crate::{format_date, PlaylistItem};
+
+// Extended M3U. The #EXTVLCOPT lines are input options that VLC applies to the item.
+pub fn render(playlist: &[PlaylistItem], ctx: &Context) -> String {
+  let mut m3u = String::from("#EXTM3U\n");
+  for item in playlist.iter() {
+    let title = match item.artist.as_ref() {
+      Some(artist) => format!("{} - {}", artist, item.name),
+      None => item.name.clone(),
+    };
+    m3u.push_str(&format!(
+      "#EXTINF:{},{}\n",
+      item.duration.map(|d| d as i64).unwrap_or(-1),
+      single_line(&title)
+    ));
+    if let Some(artist) = item.artist.as_ref() {
+      m3u.push_str(&format!("#EXTART:{}\n", single_line(artist)));
+      m3u.push_str(&format!("#EXTALBUMARTIST:{}\n", single_line(artist)));
+    }
+    if let Some(genre) = item.genre.as_ref() {
+      m3u.push_str(&format!("#EXTGENRE:{}\n", single_line(genre)));
+    }
+    m3u.push_str(&format!(
+      "#EXTVLCOPT:meta-title={}\n",
+      single_line(&item.name)
+    ));
+    if let Some(artist) = item.artist.as_ref() {
+      m3u.push_str(&format!("#EXTVLCOPT:meta-artist={}\n", single_line(artist)));
+    }
+    if let Some(genre) = item.genre.as_ref() {
+      m3u.push_str(&format!("#EXTVLCOPT:meta-genre={}\n", single_line(genre)));
+    }
+    if let Some(date) = item.date.as_ref() {
+      m3u.push_str(&format!("#EXTVLCOPT:meta-date={}\n", format_date(date)));
+    }
+    if let Some(description) = item.description.as_ref() {
+      m3u.push_str(&format!(
+        "#EXTVLCOPT:meta-description={}\n",
+        single_line(description)
+      ));
+    }
+    if let Some(language) = item.language.as_ref() {
+      m3u.push_str(&format!(
+        "#EXTVLCOPT:audio-language={}\n",
+        single_line(language)
+      ));
+    }
+    if let Some(start_time) = item.start_time {
+      m3u.push_str(&format!("#EXTVLCOPT:start-time={}\n", start_time));
+    }
+    m3u.push_str(&ctx.item_url(item, Output::M3u));
+    m3u.push('\n');
+  }
+  return m3u;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ItemKind;
+
+  #[test]
+  fn test() {
+    let playlist = vec![
+      PlaylistItem {
+        description: Some(String::from("Line one\r\nLine two")),
+        language: Some(String::from("en")),
+        artist: Some(String::from("GamesDoneQuick")),
+        genre: Some(String::from("System Shock 2")),
+        date: Some("2017-01-10T20:10:16Z".parse().unwrap()),
+        duration: Some(118070),
+        ..PlaylistItem::test(
+          ItemKind::Vod,
+          "https://usher.ttvnw.net/vod/113837699.m3u8",
+          "AGDQ 2017 - Mickey's Dangerous Chase",
+        )
+      },
+      // Videos in listings and "Load more" are resolved when they are played
+      PlaylistItem {
+        id: Some(String::from("https://www.twitch.tv/videos/113837699")),
+        ..PlaylistItem::test(
+          ItemKind::Vod,
+          "https://www.twitch.tv/videos/113837699",
+          "AGDQ 2017",
+        )
+      },
+      PlaylistItem::test(
+        ItemKind::Pagination,
+        "https://www.twitch.tv/gamesdonequick/videos?cursor=abc",
+        "Load more",
+      ),
+    ];
+    let ctx = Context::test("https://www.twitch.tv/gamesdonequick/videos", None);
+    assert_eq!(
+      render(&playlist, &ctx),
+      "#EXTM3U
+#EXTINF:118070,GamesDoneQuick - AGDQ 2017 - Mickey's Dangerous Chase
+#EXTART:GamesDoneQuick
+#EXTALBUMARTIST:GamesDoneQuick
+#EXTGENRE:System Shock 2
+#EXTVLCOPT:meta-title=AGDQ 2017 - Mickey's Dangerous Chase
+#EXTVLCOPT:meta-artist=GamesDoneQuick
+#EXTVLCOPT:meta-genre=System Shock 2
+#EXTVLCOPT:meta-date=2017-01-10 20:10:16
+#EXTVLCOPT:meta-description=Line one Line two
+#EXTVLCOPT:audio-language=en
+https://usher.ttvnw.net/vod/113837699.m3u8
+#EXTINF:-1,AGDQ 2017
+#EXTVLCOPT:meta-title=AGDQ 2017
+http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fvideos%2F113837699
+#EXTINF:-1,Load more
+#EXTVLCOPT:meta-title=Load more
+http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fgamesdonequick%2Fvideos%3Fcursor%3Dabc&output=m3u
+"
+    );
+  }
+}
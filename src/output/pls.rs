@@ -0,0 +1,74 @@
+use super::{single_line, Context, Output};
+use crate::PlaylistItem;
+
+// https://en.wikipedia.org/wiki/PLS_(file_format)
+pub fn render(playlist: &[PlaylistItem], ctx: &Context) -> String {
+  let mut pls = String::from("[playlist]\n");
+  for (i, item) in playlist.iter().enumerate() {
+    let n = i + 1;
+    let title = match item.artist.as_ref() {
+      Some(artist) => format!("{} - {}", artist, item.name),
+      None => item.name.clone(),
+    };
+    pls.push_str(&format!("File{}={}\n", n, ctx.item_url(item, Output::Pls)));
+    pls.push_str(&format!("Title{}={}\n", n, single_line(&title)));
+    pls.push_str(&format!(
+      "Length{}={}\n",
+      n,
+      item.duration.map(|d| d as i64).unwrap_or(-1)
+    ));
+  }
+  pls.push_str(&format!("NumberOfEntries={}\n", playlist.len()));
+  pls.push_str("Version=2\n");
+  return pls;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ItemKind;
+
+  #[test]
+  fn test() {
+    let playlist = vec![
+      PlaylistItem {
+        artist: Some(String::from("GamesDoneQuick")),
+        duration: Some(118070),
+        ..PlaylistItem::test(
+          ItemKind::Vod,
+          "https://usher.ttvnw.net/vod/113837699.m3u8",
+          "AGDQ 2017 - Mickey's\nDangerous Chase",
+        )
+      },
+      PlaylistItem::test(
+        ItemKind::Live,
+        "https://usher.ttvnw.net/api/channel/hls/speedgaming.m3u8",
+        "Speedgaming",
+      ),
+    ];
+    let ctx = Context::test("https://www.twitch.tv/videos/113837699", None);
+    assert_eq!(
+      render(&playlist, &ctx),
+      "[playlist]
+File1=https://usher.ttvnw.net/vod/113837699.m3u8
+Title1=GamesDoneQuick - AGDQ 2017 - Mickey's Dangerous Chase
+Length1=118070
+File2=https://usher.ttvnw.net/api/channel/hls/speedgaming.m3u8
+Title2=Speedgaming
+Length2=-1
+NumberOfEntries=2
+Version=2
+"
+    );
+
+    let response = super::super::render(Output::Pls, &playlist[..1], &ctx);
+    assert_eq!(
+      response.headers().get("content-type").unwrap(),
+      "audio/x-scpls; charset=utf-8"
+    );
+    assert_eq!(
+      response.headers().get("content-disposition").unwrap(),
+      "inline; filename=\"AGDQ 2017 - Mickey's_Dangerous Chase.pls\"; filename*=UTF-8''AGDQ%202017%20-%20Mickey%27s_Dangerous%20Chase.pls"
+    );
+  }
+}
@@ -0,0 +1,170 @@
+use super::feed::{item_id, media_type, Feed};
+use super::{xml_escape, Context, Output};
+use crate::PlaylistItem;
+
+// RSS 2.0 with iTunes podcast tags. The enclosures link back to /resolve so that the access tokens are fresh.
+// https://www.rssboard.org/rss-specification
+// https://podcasters.apple.com/support/823-podcast-requirements
+pub fn render(playlist: &[PlaylistItem], ctx: &Context) -> String {
+  let feed = Feed::new(playlist, ctx, Output::Rss);
+
+  let mut rss = String::from(
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd" xmlns:atom="http://www.w3.org/2005/Atom">
+  <channel>
+"#,
+  );
+  rss.push_str(&element(2, "title", feed.title));
+  rss.push_str(&element(2, "link", feed.home_page_url));
+  rss.push_str(&element(2, "description", feed.description));
+  rss.push_str(&format!(
+    "    <atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\" />\n",
+    xml_escape(&feed.feed_url)
+  ));
+  if let Some(next_url) = feed.next_url.as_ref() {
+    rss.push_str(&format!(
+      "    <atom:link href=\"{}\" rel=\"next\" type=\"application/rss+xml\" />\n",
+      xml_escape(next_url)
+    ));
+  }
+  if let Some(language) = feed.language {
+    rss.push_str(&element(2, "language", language));
+  }
+  if let Some(author) = feed.author {
+    rss.push_str(&element(2, "itunes:author", author));
+  }
+  rss.push_str(&element(2, "itunes:explicit", "false"));
+
+  for item in feed.items.iter() {
+    rss.push_str("    <item>\n");
+    rss.push_str(&element(3, "title", &item.name));
+    rss.push_str(&element(3, "link", &item.path));
+    rss.push_str(&format!(
+      "      <guid isPermaLink=\"{}\">{}</guid>\n",
+      item_id(item).starts_with("http"),
+      xml_escape(item_id(item))
+    ));
+    if let Some(description) = item.description.as_ref() {
+      rss.push_str(&element(3, "description", description));
+    }
+    if let Some(date) = item.date.as_ref() {
+      rss.push_str(&element(3, "pubDate", &date.to_rfc2822()));
+    }
+    if let Some(genre) = item.genre.as_ref() {
+      rss.push_str(&element(3, "category", genre));
+    }
+    rss.push_str(&format!(
+      "      <enclosure url=\"{}\" length=\"0\" type=\"{}\" />\n",
+      xml_escape(&ctx.item_url(item, Output::Rss)),
+      media_type(item)
+    ));
+    if let Some(artist) = item.artist.as_ref() {
+      rss.push_str(&element(3, "itunes:author", artist));
+    }
+    if let Some(duration) = item.duration {
+      rss.push_str(&element(3, "itunes:duration", &duration.to_string()));
+    }
+    rss.push_str("    </item>\n");
+  }
+
+  rss.push_str("  </channel>\n</rss>\n");
+  return rss;
+}
+
+fn element(depth: usize, name: &str, value: &str) -> String {
+  return format!(
+    "{}<{}>{}</{}>\n",
+    "  ".repeat(depth),
+    name,
+    xml_escape(value),
+    name
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ItemKind;
+
+  #[test]
+  fn test() {
+    let playlist = vec![
+      PlaylistItem {
+        id: Some(String::from("https://www.twitch.tv/videos/113837699")),
+        language: Some(String::from("en")),
+        artist: Some(String::from("GamesDoneQuick")),
+        genre: Some(String::from("System Shock 2")),
+        date: Some("2017-01-10T20:10:16Z".parse().unwrap()),
+        duration: Some(118070),
+        ..PlaylistItem::test(
+          ItemKind::Vod,
+          "https://www.twitch.tv/videos/113837699",
+          "AGDQ 2017 - Mickey's Dangerous Chase",
+        )
+      },
+      PlaylistItem {
+        id: Some(String::from(
+          "https://clips.twitch.tv/AwkwardHelplessSalamanderSwiftRage",
+        )),
+        language: Some(String::from("en")),
+        artist: Some(String::from("GamesDoneQuick")),
+        duration: Some(30),
+        ..PlaylistItem::test(
+          ItemKind::Clip,
+          "https://clips.twitch.tv/AwkwardHelplessSalamanderSwiftRage",
+          "Clip",
+        )
+      },
+      PlaylistItem {
+        artist: Some(String::from("GamesDoneQuick")),
+        ..PlaylistItem::test(
+          ItemKind::Pagination,
+          "https://www.twitch.tv/gamesdonequick/videos?filter=all&sort=time&cursor=abc",
+          "Load more",
+        )
+      },
+    ];
+    let ctx = Context {
+      description: Some("Videos by GamesDoneQuick"),
+      ..Context::test(
+        "https://www.twitch.tv/gamesdonequick/videos",
+        Some("GamesDoneQuick"),
+      )
+    };
+    assert_eq!(
+      render(&playlist, &ctx),
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd" xmlns:atom="http://www.w3.org/2005/Atom">
+  <channel>
+    <title>GamesDoneQuick</title>
+    <link>https://www.twitch.tv/gamesdonequick/videos</link>
+    <description>Videos by GamesDoneQuick</description>
+    <atom:link href="http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fgamesdonequick%2Fvideos&amp;output=rss" rel="self" type="application/rss+xml" />
+    <atom:link href="http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fgamesdonequick%2Fvideos%3Ffilter%3Dall%26sort%3Dtime%26cursor%3Dabc&amp;output=rss" rel="next" type="application/rss+xml" />
+    <language>en</language>
+    <itunes:author>GamesDoneQuick</itunes:author>
+    <itunes:explicit>false</itunes:explicit>
+    <item>
+      <title>AGDQ 2017 - Mickey&apos;s Dangerous Chase</title>
+      <link>https://www.twitch.tv/videos/113837699</link>
+      <guid isPermaLink="true">https://www.twitch.tv/videos/113837699</guid>
+      <pubDate>Tue, 10 Jan 2017 20:10:16 +0000</pubDate>
+      <category>System Shock 2</category>
+      <enclosure url="http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fvideos%2F113837699" length="0" type="application/x-mpegURL" />
+      <itunes:author>GamesDoneQuick</itunes:author>
+      <itunes:duration>118070</itunes:duration>
+    </item>
+    <item>
+      <title>Clip</title>
+      <link>https://clips.twitch.tv/AwkwardHelplessSalamanderSwiftRage</link>
+      <guid isPermaLink="true">https://clips.twitch.tv/AwkwardHelplessSalamanderSwiftRage</guid>
+      <enclosure url="http://localhost:8080/resolve?url=https%3A%2F%2Fclips.twitch.tv%2FAwkwardHelplessSalamanderSwiftRage" length="0" type="video/mp4" />
+      <itunes:author>GamesDoneQuick</itunes:author>
+      <itunes:duration>30</itunes:duration>
+    </item>
+  </channel>
+</rss>
+"#
+    );
+  }
+}
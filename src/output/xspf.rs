@@ -0,0 +1,99 @@
+use super::{xml_escape, Context, Output};
+use crate::{format_date, PlaylistItem};
+
+// https://xspf.org/spec
+// Genre, date, language and start time are passed as VLC input options in the VLC extension.
+pub fn render(playlist: &[PlaylistItem], ctx: &Context) -> String {
+  let mut xspf = String::from(
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist xmlns="http://xspf.org/ns/0/" xmlns:vlc="http://www.videolan.org/vlc/playlist/ns/0/" version="1">
+  <trackList>
+"#,
+  );
+  for (i, item) in playlist.iter().enumerate() {
+    xspf.push_str("    <track>\n");
+    xspf.push_str(&element("location", &ctx.item_url(item, Output::Xspf)));
+    xspf.push_str(&element("title", &item.name));
+    if let Some(artist) = item.artist.as_ref() {
+      xspf.push_str(&element("creator", artist));
+    }
+    if let Some(description) = item.description.as_ref() {
+      xspf.push_str(&element("annotation", description));
+    }
+    if let Some(duration) = item.duration {
+      xspf.push_str(&element("duration", &(duration * 1000).to_string()));
+    }
+    xspf.push_str("      <extension application=\"http://www.videolan.org/vlc/playlist/0\">\n");
+    xspf.push_str(&format!("        <vlc:id>{}</vlc:id>\n", i));
+    if let Some(genre) = item.genre.as_ref() {
+      xspf.push_str(&option("meta-genre", genre));
+    }
+    if let Some(date) = item.date.as_ref() {
+      xspf.push_str(&option("meta-date", &format_date(date)));
+    }
+    if let Some(language) = item.language.as_ref() {
+      xspf.push_str(&option("audio-language", language));
+    }
+    if let Some(start_time) = item.start_time {
+      xspf.push_str(&option("start-time", &start_time.to_string()));
+    }
+    xspf.push_str("      </extension>\n");
+    xspf.push_str("    </track>\n");
+  }
+  xspf.push_str("  </trackList>\n</playlist>\n");
+  return xspf;
+}
+
+fn element(name: &str, value: &str) -> String {
+  return format!("      <{}>{}</{}>\n", name, xml_escape(value), name);
+}
+
+fn option(name: &str, value: &str) -> String {
+  return format!(
+    "        <vlc:option>{}={}</vlc:option>\n",
+    name,
+    xml_escape(value)
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ItemKind;
+
+  #[test]
+  fn test() {
+    let playlist = vec![PlaylistItem {
+      description: Some(String::from("<Tom & Jerry>")),
+      artist: Some(String::from("GamesDoneQuick")),
+      genre: Some(String::from("System Shock 2")),
+      duration: Some(58),
+      ..PlaylistItem::test(
+        ItemKind::Pagination,
+        "https://www.twitch.tv/gamesdonequick/videos?filter=all&sort=time&cursor=abc",
+        "Load more",
+      )
+    }];
+    let ctx = Context::test("https://www.twitch.tv/gamesdonequick/videos", None);
+    assert_eq!(
+      render(&playlist, &ctx),
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist xmlns="http://xspf.org/ns/0/" xmlns:vlc="http://www.videolan.org/vlc/playlist/ns/0/" version="1">
+  <trackList>
+    <track>
+      <location>http://localhost:8080/resolve?url=https%3A%2F%2Fwww.twitch.tv%2Fgamesdonequick%2Fvideos%3Ffilter%3Dall%26sort%3Dtime%26cursor%3Dabc&amp;output=xspf</location>
+      <title>Load more</title>
+      <creator>GamesDoneQuick</creator>
+      <annotation>&lt;Tom &amp; Jerry&gt;</annotation>
+      <duration>58000</duration>
+      <extension application="http://www.videolan.org/vlc/playlist/0">
+        <vlc:id>0</vlc:id>
+        <vlc:option>meta-genre=System Shock 2</vlc:option>
+      </extension>
+    </track>
+  </trackList>
+</playlist>
+"#
+    );
+  }
+}
@@ -0,0 +1,131 @@
+use actix_web::HttpRequest;
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, Mutex},
+  time::Instant,
+};
+
+use crate::error::ResolveError;
+
+// Forget about clients that have been idle long enough to have a full bucket once there are this many
+const MAX_BUCKETS: usize = 10000;
+
+struct Bucket {
+  tokens: f64,
+  updated: Instant,
+}
+
+// Token bucket rate limiter. Each key starts with a full bucket of burst tokens that refills at a constant rate.
+pub struct RateLimiter {
+  buckets: Mutex<HashMap<String, Bucket>>,
+  rate: f64, // tokens per second
+  burst: f64,
+}
+
+impl RateLimiter {
+  pub fn new(per_minute: u32, burst: u32) -> RateLimiter {
+    RateLimiter {
+      buckets: Mutex::new(HashMap::new()),
+      rate: per_minute as f64 / 60.0,
+      burst: burst.max(1) as f64,
+    }
+  }
+
+  pub fn check(&self, key: &str) -> Result<(), ResolveError> {
+    let now = Instant::now();
+    let mut buckets = self.buckets.lock().unwrap();
+    if buckets.len() >= MAX_BUCKETS {
+      let (rate, burst) = (self.rate, self.burst);
+      buckets.retain(|_, bucket| {
+        bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate < burst
+      });
+    }
+
+    let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+      tokens: self.burst,
+      updated: now,
+    });
+    let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+    bucket.updated = now;
+
+    if bucket.tokens < 1.0 {
+      let retry_after = ((1.0 - bucket.tokens) / self.rate).ceil() as u64;
+      return Err(ResolveError::RateLimited(Some(retry_after)));
+    }
+    bucket.tokens -= 1.0;
+    return Ok(());
+  }
+}
+
+pub static CLIENT_LIMITER: LazyLock<Option<RateLimiter>> = LazyLock::new(|| {
+  if crate::CONFIG.rate_limit_per_client == 0 {
+    return None;
+  }
+  return Some(RateLimiter::new(
+    crate::CONFIG.rate_limit_per_client,
+    crate::CONFIG.rate_limit_client_burst,
+  ));
+});
+
+pub static TARGET_LIMITER: LazyLock<Option<RateLimiter>> = LazyLock::new(|| {
+  if crate::CONFIG.rate_limit_per_target == 0 {
+    return None;
+  }
+  return Some(RateLimiter::new(
+    crate::CONFIG.rate_limit_per_target,
+    crate::CONFIG.rate_limit_target_burst,
+  ));
+});
+
+// Uses the trusted proxy header if configured, otherwise the address of the peer.
+// For X-Forwarded-For the last address is used since that is the one added by the proxy.
+pub fn client_ip(req: &HttpRequest) -> String {
+  if let Some(header) = crate::CONFIG.trusted_proxy_header.as_ref() {
+    let ip = req
+      .headers()
+      .get(header.as_str())
+      .and_then(|v| v.to_str().ok())
+      .and_then(|v| v.rsplit(',').next())
+      .map(|v| v.trim())
+      .filter(|v| !v.is_empty());
+    if let Some(ip) = ip {
+      return ip.to_string();
+    }
+  }
+  return req
+    .peer_addr()
+    .map(|addr| addr.ip().to_string())
+    .unwrap_or_default();
+}
+
+pub fn check_client(req: &HttpRequest) -> Result<(), ResolveError> {
+  match CLIENT_LIMITER.as_ref() {
+    Some(limiter) => limiter.check(&client_ip(req)),
+    None => Ok(()),
+  }
+}
+
+pub fn check_target(key: &str) -> Result<(), ResolveError> {
+  match TARGET_LIMITER.as_ref() {
+    Some(limiter) => limiter.check(key),
+    None => Ok(()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test() {
+    let limiter = RateLimiter::new(60, 2);
+    assert!(limiter.check("127.0.0.1").is_ok());
+    assert!(limiter.check("127.0.0.1").is_ok());
+    match limiter.check("127.0.0.1") {
+      Err(ResolveError::RateLimited(Some(retry_after))) => assert_eq!(retry_after, 1),
+      ret => panic!("unexpected result: {:?}", ret),
+    }
+    assert!(limiter.check("127.0.0.2").is_ok());
+  }
+}
@@ -0,0 +1,223 @@
+use async_trait::async_trait;
+use std::{
+  any::Any,
+  fmt::Debug,
+  hash::{Hash, Hasher},
+  sync::{Arc, OnceLock},
+  time::Instant,
+};
+
+use crate::access;
+use crate::cache::{CacheStatus, CACHE};
+use crate::coalesce::INFLIGHT;
+use crate::error::ResolveError;
+use crate::hls;
+use crate::metrics;
+use crate::ratelimit;
+use crate::twitch;
+use crate::Playlist;
+
+// What a resolver has recognized in a URL. Each resolver has its own type for this, e.g. TwitchMatch.
+pub trait ResolverMatch: AsAny + Debug + Send + Sync {
+  // Short identifier of what kind of URL was matched, e.g. "channel"
+  fn kind(&self) -> &'static str;
+
+  // The name or id that the URL refers to
+  fn target(&self) -> &str;
+
+  // Identifies the match for the cache and the coalescing of requests. Matches with the same key are served the
+  // same playlist, so it must include everything that the result depends on.
+  fn key(&self) -> String;
+
+  // True if the URL lists other URLs, e.g. the videos of a channel
+  fn is_listing(&self) -> bool {
+    false
+  }
+
+  // True if a quality can be picked from the resolved playlist
+  fn has_variants(&self) -> bool {
+    false
+  }
+}
+
+// Lets a Match be turned back into the type of the resolver that created it
+pub trait AsAny {
+  fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+}
+
+// A URL that a resolver has recognized. Matches are compared by resolver and the key of the resolver's match,
+// which is what the cache and the coalescing of requests are keyed by.
+#[derive(Debug, Clone)]
+pub struct Match {
+  resolver: &'static str,
+  key: String,
+  inner: Arc<dyn ResolverMatch>,
+}
+
+impl Match {
+  pub fn new<M: ResolverMatch + 'static>(resolver: &'static str, m: M) -> Match {
+    Match {
+      resolver,
+      key: m.key(),
+      inner: Arc::new(m),
+    }
+  }
+
+  // The match of the resolver that created it
+  pub fn downcast_ref<M: ResolverMatch + 'static>(&self) -> Option<&M> {
+    return self.inner.as_ref().as_any().downcast_ref::<M>();
+  }
+
+  pub fn kind(&self) -> &'static str {
+    return self.inner.kind();
+  }
+
+  pub fn target(&self) -> &str {
+    return self.inner.target();
+  }
+
+  pub fn is_listing(&self) -> bool {
+    return self.inner.is_listing();
+  }
+
+  pub fn has_variants(&self) -> bool {
+    return self.inner.has_variants();
+  }
+}
+
+impl PartialEq for Match {
+  fn eq(&self, other: &Match) -> bool {
+    return self.resolver == other.resolver && self.key == other.key;
+  }
+}
+
+impl Eq for Match {}
+
+impl Hash for Match {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.resolver.hash(state);
+    self.key.hash(state);
+  }
+}
+
+#[async_trait]
+pub trait Resolver: Send + Sync {
+  // Short identifier used in logs
+  fn name(&self) -> &'static str;
+
+  // The hosts of the URLs that probe can match, used by the VLC script
+  fn hosts(&self) -> &'static [&'static str];
+
+  // Returns a match if this resolver knows how to handle the URL
+  fn probe(&self, url: &str) -> Option<Match>;
+
+  // Resolves a match returned by probe into playlist items
+  async fn resolve(&self, m: Match) -> Result<Playlist, ResolveError>;
+
+  // The position in seconds that the URL asks videos to start at, kept out of the match so it doesn't split the cache
+  fn start_time(&self, _url: &str) -> Option<usize> {
+    None
+  }
+
+  // Performs a cheap request to verify that the upstream service is reachable and accepts our configuration
+  async fn health_check(&self) -> Result<(), ResolveError> {
+    Ok(())
+  }
+}
+
+// The resolvers are probed in order and the first match wins.
+pub fn resolvers() -> &'static [Box<dyn Resolver>] {
+  static RESOLVERS: OnceLock<Vec<Box<dyn Resolver>>> = OnceLock::new();
+  RESOLVERS.get_or_init(|| vec![Box::new(twitch::Twitch)])
+}
+
+pub fn probe(url: &str) -> Option<(&'static dyn Resolver, Match)> {
+  for resolver in resolvers().iter() {
+    if let Some(m) = resolver.probe(url) {
+      return Some((resolver.as_ref(), m));
+    }
+  }
+  return None;
+}
+
+// Checks the access rules, the cache and the rate limits before handing the match to its resolver.
+// Concurrent requests for the same match share one call to the resolver. With variants, the HLS master playlists
+// are fetched as well, and cached with the rest of the playlist.
+pub async fn resolve(
+  resolver: &dyn Resolver,
+  m: Match,
+  variants: bool,
+) -> Result<(Playlist, CacheStatus), ResolveError> {
+  let kind = m.kind();
+  let start = Instant::now();
+  let ret = resolve_match(resolver, m, variants).await;
+
+  metrics::RESOLVE_DURATION
+    .with_label_values(&[resolver.name(), kind])
+    .observe(start.elapsed().as_secs_f64());
+  let outcome = match &ret {
+    Ok(_) => "ok",
+    Err(e) => e.code(),
+  };
+  metrics::RESOLVES
+    .with_label_values(&[resolver.name(), kind, outcome])
+    .inc();
+  if let Ok((_, cache_status)) = &ret {
+    metrics::CACHE
+      .with_label_values(&[cache_status.as_str().to_lowercase().as_str()])
+      .inc();
+  }
+  return ret;
+}
+
+async fn resolve_match(
+  resolver: &dyn Resolver,
+  m: Match,
+  variants: bool,
+) -> Result<(Playlist, CacheStatus), ResolveError> {
+  access::check(&m)?;
+  if let Some(playlist) = CACHE.get(&m) {
+    if !variants || !hls::needs_variants(&playlist) {
+      return Ok((playlist, CacheStatus::Hit));
+    }
+  }
+  let (ret, coalesced) = INFLIGHT.run(&m, || fetch(resolver, &m, variants)).await;
+  let mut playlist = ret?;
+  if variants && hls::needs_variants(&playlist) {
+    // Shared the result of a request without a quality, which didn't fetch the variants
+    let (ret, _) = INFLIGHT.run(&m, || fetch(resolver, &m, variants)).await;
+    playlist = ret?;
+  }
+  let cache_status = if coalesced {
+    CacheStatus::Coalesced
+  } else {
+    CacheStatus::Miss
+  };
+  return Ok((playlist, cache_status));
+}
+
+// Resolves the match unless it is cached, and fetches the variants that are missing
+async fn fetch(
+  resolver: &dyn Resolver,
+  m: &Match,
+  variants: bool,
+) -> Result<Playlist, ResolveError> {
+  let cached = CACHE.get(m);
+  // Only requests that reach the upstream service count towards the per-target limit
+  ratelimit::check_target(&format!("{}/{}/{}", resolver.name(), m.kind(), m.target()))?;
+  let mut playlist = match cached {
+    Some(playlist) => playlist,
+    None => resolver.resolve(m.clone()).await?,
+  };
+  if variants {
+    hls::load_variants(&mut playlist).await?;
+  }
+  CACHE.insert(m.clone(), &playlist);
+  return Ok(playlist);
+}
@@ -1,12 +1,15 @@
-use log;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use regex::Regex;
 use reqwest::StatusCode;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
-use std::{result::Result, sync::OnceLock};
-use urlencoding;
+use std::{collections::HashMap, fmt::Debug, result::Result, sync::OnceLock};
 
-use crate::PlaylistItem;
+use crate::client;
+use crate::error::ResolveError;
+use crate::resolver::{Match, Resolver, ResolverMatch};
+use crate::{ItemKind, Playlist, PlaylistItem};
 
 const GRAPHQL_URL: &str = "https://gql.twitch.tv/gql";
 
@@ -36,6 +39,54 @@ pub fn channel_videos_url_patterns() -> &'static [Regex] {
   })
 }
 
+pub fn channel_clips_url_patterns() -> &'static [Regex] {
+  static CHANNEL_CLIPS_URL_PATTERNS: OnceLock<[Regex; 1]> = OnceLock::new();
+  CHANNEL_CLIPS_URL_PATTERNS.get_or_init(|| {
+    [
+      // https://www.twitch.tv/speedgaming/clips
+      // https://www.twitch.tv/speedgaming/clips?filter=clips&range=7d
+      // https://www.twitch.tv/speedgaming/clips?filter=clips&range=all&cursor=MjA=
+      Regex::new(r"^https?://www\.twitch\.tv/(?P<channel_name>[^/?#]+)/clips(?:[?&#](?:filter=[^&#]+|range=(?P<range>[^&#]+)|cursor=(?P<cursor>[^&#]+)))*").unwrap(),
+    ]
+  })
+}
+
+pub fn category_url_patterns() -> &'static [Regex] {
+  static CATEGORY_URL_PATTERNS: OnceLock<[Regex; 1]> = OnceLock::new();
+  CATEGORY_URL_PATTERNS.get_or_init(|| {
+    [
+      // https://www.twitch.tv/directory/category/super-mario-64
+      // https://www.twitch.tv/directory/category/super-mario-64?cursor=eyJzIjozMH0=
+      // https://www.twitch.tv/directory/game/Super%20Mario%2064 (legacy url)
+      Regex::new(r"^https?://www\.twitch\.tv/directory/(?:category/(?P<slug>[^/?#]+)|game/(?P<name>[^/?#]+))/?(?:[?&#](?:sort=[^&#]+|cursor=(?P<cursor>[^&#]+)))*").unwrap(),
+    ]
+  })
+}
+
+pub fn category_clips_url_patterns() -> &'static [Regex] {
+  static CATEGORY_CLIPS_URL_PATTERNS: OnceLock<[Regex; 1]> = OnceLock::new();
+  CATEGORY_CLIPS_URL_PATTERNS.get_or_init(|| {
+    [
+      // https://www.twitch.tv/directory/category/super-mario-64/clips?range=7d
+      // https://www.twitch.tv/directory/game/Super%20Mario%2064/clips?range=all (legacy url)
+      Regex::new(r"^https?://www\.twitch\.tv/directory/(?:category/(?P<slug>[^/?#]+)|game/(?P<name>[^/?#]+))/clips(?:[?&#](?:range=(?P<range>[^&#]+)|cursor=(?P<cursor>[^&#]+)))*").unwrap(),
+    ]
+  })
+}
+
+pub fn category_videos_url_patterns() -> &'static [Regex] {
+  static CATEGORY_VIDEOS_URL_PATTERNS: OnceLock<[Regex; 1]> = OnceLock::new();
+  CATEGORY_VIDEOS_URL_PATTERNS.get_or_init(|| {
+    [
+      // https://www.twitch.tv/directory/category/super-mario-64/videos
+      // https://www.twitch.tv/directory/category/super-mario-64/videos/all?sort=time
+      // https://www.twitch.tv/directory/category/super-mario-64/videos/archive?sort=views
+      // https://www.twitch.tv/directory/game/Super%20Mario%2064/videos/highlight (legacy url)
+      Regex::new(r"^https?://www\.twitch\.tv/directory/(?:category/(?P<slug>[^/?#]+)|game/(?P<name>[^/?#]+))/videos(?:/(?P<filter>[^/?#]+))?(?:[?&#](?:sort=(?P<sort>[^&#]+)|cursor=(?P<cursor>[^&#]+)))*").unwrap(),
+    ]
+  })
+}
+
 pub fn video_url_patterns() -> &'static [Regex] {
   static VIDEO_URL_PATTERNS: OnceLock<[Regex; 3]> = OnceLock::new();
   VIDEO_URL_PATTERNS.get_or_init(|| {
@@ -63,20 +114,173 @@ pub fn clip_url_patterns() -> &'static [Regex] {
   })
 }
 
-#[derive(Debug)]
+pub struct Twitch;
+
+#[async_trait]
+impl Resolver for Twitch {
+  fn name(&self) -> &'static str {
+    "twitch"
+  }
+
+  fn hosts(&self) -> &'static [&'static str] {
+    &["www.twitch.tv", "player.twitch.tv", "clips.twitch.tv"]
+  }
+
+  fn probe(&self, url: &str) -> Option<Match> {
+    probe(url).map(|m| Match::new(self.name(), m))
+  }
+
+  async fn resolve(&self, m: Match) -> Result<Playlist, ResolveError> {
+    match m.downcast_ref::<TwitchMatch>() {
+      Some(m) => resolve(m.clone()).await,
+      None => Err(ResolveError::Misconfigured(format!(
+        "twitch can't resolve {:?}",
+        m
+      ))),
+    }
+  }
+
+  fn start_time(&self, url: &str) -> Option<usize> {
+    match probe(url) {
+      Some(TwitchMatch::Video(..)) => start_time(url),
+      _ => None,
+    }
+  }
+
+  async fn health_check(&self) -> Result<(), ResolveError> {
+    let _: HealthData = graphql(include_str!("twitch/health.gql"), json!({})).await?;
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 pub enum TwitchMatch {
   Channel(String),
   ChannelVideos(String, String, String, Option<String>),
-  Video(String),
+  ChannelClips(String, String, Option<String>), // channel name, range and cursor
+  Video(String),                                // video id
   Clip(String),
+  CategoryStreams(Category, Option<String>), // category and cursor
+  CategoryClips(Category, String, Option<String>), // category, range and cursor
+  CategoryVideos(Category, String, String, Option<String>), // category, filter, sort and cursor
+}
+
+// Categories are identified by their slug, or by their name in legacy URLs
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum Category {
+  Slug(String),
+  Name(String),
+}
+
+impl Category {
+  fn from_captures(captures: &regex::Captures) -> Category {
+    if let Some(slug) = captures.name("slug") {
+      return Category::Slug(slug.as_str().to_lowercase());
+    }
+    let name = captures.name("name").unwrap().as_str();
+    return Category::Name(
+      urlencoding::decode(name)
+        .map(|name| name.into_owned())
+        .unwrap_or(name.to_string()),
+    );
+  }
+
+  fn as_str(&self) -> &str {
+    match self {
+      Category::Slug(slug) => slug,
+      Category::Name(name) => name,
+    }
+  }
+
+  fn url(&self) -> String {
+    match self {
+      Category::Slug(slug) => format!("https://www.twitch.tv/directory/category/{}", slug),
+      Category::Name(name) => format!(
+        "https://www.twitch.tv/directory/game/{}",
+        urlencoding::encode(name)
+      ),
+    }
+  }
+
+  // The GraphQL variables that identify the category
+  fn variables(&self) -> serde_json::Value {
+    match self {
+      Category::Slug(slug) => json!({ "slug": slug }),
+      Category::Name(name) => json!({ "name": name }),
+    }
+  }
+}
+
+impl ResolverMatch for TwitchMatch {
+  fn kind(&self) -> &'static str {
+    match self {
+      TwitchMatch::Channel(..) => "channel",
+      TwitchMatch::ChannelVideos(..) => "channel_videos",
+      TwitchMatch::ChannelClips(..) => "channel_clips",
+      TwitchMatch::Video(..) => "video",
+      TwitchMatch::Clip(..) => "clip",
+      TwitchMatch::CategoryStreams(..) => "category",
+      TwitchMatch::CategoryClips(..) => "category_clips",
+      TwitchMatch::CategoryVideos(..) => "category_videos",
+    }
+  }
+
+  fn is_listing(&self) -> bool {
+    matches!(
+      self,
+      TwitchMatch::ChannelVideos(..)
+        | TwitchMatch::ChannelClips(..)
+        | TwitchMatch::CategoryStreams(..)
+        | TwitchMatch::CategoryClips(..)
+        | TwitchMatch::CategoryVideos(..)
+    )
+  }
+
+  // Live streams and videos are HLS master playlists with multiple qualities, clips are not
+  fn has_variants(&self) -> bool {
+    matches!(self, TwitchMatch::Channel(..) | TwitchMatch::Video(..))
+  }
+
+  // The channel name, video id, clip slug or category
+  fn target(&self) -> &str {
+    match self {
+      TwitchMatch::Channel(channel_name) => channel_name,
+      TwitchMatch::ChannelVideos(channel_name, ..) => channel_name,
+      TwitchMatch::ChannelClips(channel_name, ..) => channel_name,
+      TwitchMatch::Video(video_id) => video_id,
+      TwitchMatch::Clip(slug) => slug,
+      TwitchMatch::CategoryStreams(category, ..) => category.as_str(),
+      TwitchMatch::CategoryClips(category, ..) => category.as_str(),
+      TwitchMatch::CategoryVideos(category, ..) => category.as_str(),
+    }
+  }
+
+  // Every field as JSON, e.g. {"ChannelClips":["speedgaming","30d",null]}
+  fn key(&self) -> String {
+    serde_json::to_string(self).unwrap()
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLResponse<T> {
+  data: Option<T>,
+  #[serde(default)]
+  errors: Vec<GraphQLError>,
 }
 
-// Channel
 #[derive(Debug, Deserialize)]
-struct ChannelResponseData {
-  data: ChannelData,
+struct GraphQLError {
+  message: String,
 }
 
+// Health
+#[derive(Debug, Deserialize)]
+struct HealthData {
+  #[serde(rename = "__typename")]
+  _typename: String,
+}
+
+// Channel
 #[derive(Debug, Deserialize)]
 struct ChannelData {
   channel: Option<Channel>,
@@ -101,21 +305,87 @@ struct Stream {
 
 // ChannelVideos
 #[derive(Debug, Deserialize)]
-struct ChannelVideosResponseData {
-  data: ChannelVideosData,
+struct ChannelVideosData {
+  user: Option<UserWithVideos>,
 }
 
+// ChannelClips
 #[derive(Debug, Deserialize)]
-struct ChannelVideosData {
-  user: Option<UserWithVideos>,
+struct ChannelClipsData {
+  user: Option<UserWithClips>,
+}
+
+// CategoryStreams
+#[derive(Debug, Deserialize)]
+struct CategoryStreamsData {
+  game: Option<CategoryWithStreams>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CategoryWithStreams {
+  display_name: String,
+  streams: StreamConnection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamConnection {
+  edges: Vec<StreamEdge>,
+  page_info: PageInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamEdge {
+  cursor: String,
+  node: StreamNode,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamNode {
+  title: String,
+  viewers_count: u64,
+  created_at: String,
+  language: String,
+  broadcaster: Option<Broadcaster>,
 }
 
-// Video
 #[derive(Debug, Deserialize)]
-struct VideoResponseData {
-  data: VideoData,
+#[serde(rename_all = "camelCase")]
+struct Broadcaster {
+  login: String,
+  display_name: String,
+}
+
+// CategoryClips
+#[derive(Debug, Deserialize)]
+struct CategoryClipsData {
+  game: Option<CategoryWithClips>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CategoryWithClips {
+  display_name: String,
+  clips: ClipConnection,
+}
+
+// CategoryVideos
+#[derive(Debug, Deserialize)]
+struct CategoryVideosData {
+  game: Option<CategoryWithVideos>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CategoryWithVideos {
+  display_name: String,
+  videos: VideoConnection,
+}
+
+// Video
 #[derive(Debug, Deserialize)]
 struct VideoData {
   video: Option<Video>,
@@ -136,11 +406,6 @@ struct Video {
 }
 
 // Clip
-#[derive(Debug, Deserialize)]
-struct ClipResponseData {
-  data: ClipData,
-}
-
 #[derive(Debug, Deserialize)]
 struct ClipData {
   clip: Option<Clip>,
@@ -161,6 +426,12 @@ struct Clip {
 #[derive(Debug, Deserialize)]
 struct ClipTokenValue {
   clip_uri: String,
+  expires: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenValue {
+  expires: Option<u64>,
 }
 
 // Shared
@@ -197,6 +468,39 @@ struct VideoEdge {
   node: Video,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserWithClips {
+  display_name: String,
+  clips: ClipConnection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClipConnection {
+  edges: Vec<ClipEdge>,
+  page_info: PageInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClipEdge {
+  cursor: String,
+  node: ClipNode,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClipNode {
+  slug: String,
+  title: String,
+  game: Option<Game>,
+  created_at: String,
+  duration_seconds: usize,
+  language: String,
+  broadcaster: Option<User>, // only queried for categories
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct PageInfo {
@@ -210,18 +514,16 @@ struct PlaybackAccessToken {
 }
 
 pub fn probe(url: &str) -> Option<TwitchMatch> {
-  if crate::CONFIG.twitch_client_id.is_none() {
-    return None;
-  }
+  // Twitch URLs are not handled until a client id has been configured
+  crate::CONFIG.twitch_client_id.as_ref()?;
 
   for re in clip_url_patterns().iter() {
     if cfg!(debug_assertions) {
       log::info!("re: {:?}", re);
     }
-    let ret = re.captures(url);
-    if ret.is_some() {
+    if let Some(captures) = re.captures(url) {
       return Some(TwitchMatch::Clip(
-        ret.unwrap().get(1).unwrap().as_str().to_string(),
+        captures.get(1).unwrap().as_str().to_string(),
       ));
     }
   }
@@ -230,10 +532,61 @@ pub fn probe(url: &str) -> Option<TwitchMatch> {
     if cfg!(debug_assertions) {
       log::info!("re: {:?}", re);
     }
-    let ret = re.captures(url);
-    if ret.is_some() {
+    if let Some(captures) = re.captures(url) {
       return Some(TwitchMatch::Video(
-        ret.unwrap().get(1).unwrap().as_str().to_string(),
+        captures.get(1).unwrap().as_str().to_string(),
+      ));
+    }
+  }
+
+  // The directory has to be checked before the channel patterns, which would otherwise match it as a channel
+  for re in category_clips_url_patterns().iter() {
+    if cfg!(debug_assertions) {
+      log::info!("re: {:?}", re);
+    }
+    if let Some(captures) = re.captures(url) {
+      let range = clips_range(captures.name("range").map(|m| m.as_str()));
+      let cursor = captures.name("cursor").map(|m| m.as_str().to_string());
+      return Some(TwitchMatch::CategoryClips(
+        Category::from_captures(&captures),
+        range,
+        cursor,
+      ));
+    }
+  }
+
+  for re in category_videos_url_patterns().iter() {
+    if cfg!(debug_assertions) {
+      log::info!("re: {:?}", re);
+    }
+    if let Some(captures) = re.captures(url) {
+      let filter = captures
+        .name("filter")
+        .map(|m| m.as_str().to_string())
+        .unwrap_or("all".to_string());
+      let sort = captures
+        .name("sort")
+        .map(|m| m.as_str().to_string())
+        .unwrap_or("time".to_string());
+      let cursor = captures.name("cursor").map(|m| m.as_str().to_string());
+      return Some(TwitchMatch::CategoryVideos(
+        Category::from_captures(&captures),
+        filter,
+        sort,
+        cursor,
+      ));
+    }
+  }
+
+  for re in category_url_patterns().iter() {
+    if cfg!(debug_assertions) {
+      log::info!("re: {:?}", re);
+    }
+    if let Some(captures) = re.captures(url) {
+      let cursor = captures.name("cursor").map(|m| m.as_str().to_string());
+      return Some(TwitchMatch::CategoryStreams(
+        Category::from_captures(&captures),
+        cursor,
       ));
     }
   }
@@ -242,10 +595,12 @@ pub fn probe(url: &str) -> Option<TwitchMatch> {
     if cfg!(debug_assertions) {
       log::info!("re: {:?}", re);
     }
-    let ret = re.captures(url);
-    if ret.is_some() {
-      let captures = ret.unwrap();
-      let channel_name = captures.name("channel_name").unwrap().as_str().to_string();
+    if let Some(captures) = re.captures(url) {
+      let channel_name = captures
+        .name("channel_name")
+        .unwrap()
+        .as_str()
+        .to_lowercase();
       let filter = captures
         .name("filter")
         .map(|m| m.as_str().to_string())
@@ -264,14 +619,29 @@ pub fn probe(url: &str) -> Option<TwitchMatch> {
     }
   }
 
+  for re in channel_clips_url_patterns().iter() {
+    if cfg!(debug_assertions) {
+      log::info!("re: {:?}", re);
+    }
+    if let Some(captures) = re.captures(url) {
+      let channel_name = captures
+        .name("channel_name")
+        .unwrap()
+        .as_str()
+        .to_lowercase();
+      let range = clips_range(captures.name("range").map(|m| m.as_str()));
+      let cursor = captures.name("cursor").map(|m| m.as_str().to_string());
+      return Some(TwitchMatch::ChannelClips(channel_name, range, cursor));
+    }
+  }
+
   for re in channel_url_patterns().iter() {
     if cfg!(debug_assertions) {
       log::info!("re: {:?}", re);
     }
-    let ret = re.captures(url);
-    if ret.is_some() {
+    if let Some(captures) = re.captures(url) {
       return Some(TwitchMatch::Channel(
-        ret.unwrap().get(1).unwrap().as_str().to_lowercase(),
+        captures.get(1).unwrap().as_str().to_lowercase(),
       ));
     }
   }
@@ -279,79 +649,59 @@ pub fn probe(url: &str) -> Option<TwitchMatch> {
   return None;
 }
 
-pub async fn resolve(m: TwitchMatch) -> Result<Vec<PlaylistItem>, &'static str> {
+pub async fn resolve(m: TwitchMatch) -> Result<Playlist, ResolveError> {
   match m {
-    TwitchMatch::Channel(channel_name) => {
-      if channel_name == "twit" {
-        // These guys are responsible for most of the traffic and it is a bit annoying
-        // Until I can make this configurable in the config file, this channel will just be blocked like this
-        return Err("payment required");
-      }
-      resolve_channel(channel_name).await
-    }
+    TwitchMatch::Channel(channel_name) => resolve_channel(channel_name).await,
     TwitchMatch::ChannelVideos(channel_name, filter, sort, cursor) => {
       resolve_channel_videos(channel_name, filter, sort, cursor).await
     }
+    TwitchMatch::ChannelClips(channel_name, range, cursor) => {
+      resolve_channel_clips(channel_name, range, cursor).await
+    }
     TwitchMatch::Video(video_id) => resolve_video(video_id).await,
     TwitchMatch::Clip(slug) => resolve_clip(slug).await,
+    TwitchMatch::CategoryStreams(category, cursor) => {
+      resolve_category_streams(category, cursor).await
+    }
+    TwitchMatch::CategoryClips(category, range, cursor) => {
+      resolve_category_clips(category, range, cursor).await
+    }
+    TwitchMatch::CategoryVideos(category, filter, sort, cursor) => {
+      resolve_category_videos(category, filter, sort, cursor).await
+    }
   }
 }
 
-async fn resolve_channel(channel_name: String) -> Result<Vec<PlaylistItem>, &'static str> {
-  // https://www.twitch.tv/directory/game/Perfect%20Dark
+async fn resolve_channel(channel_name: String) -> Result<Playlist, ResolveError> {
+  // https://www.twitch.tv/directory
   // https://www.twitch.tv/recaps/annual
   if channel_name == "directory" || channel_name == "recaps" {
-    return Err("unsupported channel name");
+    return Err(ResolveError::NotFound(String::from(
+      "unsupported channel name",
+    )));
   }
 
-  let request_data = json!({
-    "query": include_str!("twitch/channel.gql"),
-    "variables": {
+  let data: ChannelData = graphql(
+    include_str!("twitch/channel.gql"),
+    json!({
       "channelName": channel_name,
       "platform": "web",
       "playerType": "site",
-    },
-  });
-
-  let client = reqwest::Client::builder()
-    .build()
-    .expect("build reqwest client");
-  let client_id = crate::CONFIG.twitch_client_id.as_ref().unwrap().as_str();
-  let response = client
-    .post(GRAPHQL_URL)
-    .header("Client-ID", client_id)
-    .body(serde_json::to_string(&request_data).unwrap())
-    .send()
-    .await
-    .expect("send graphql request");
-  let response_status = response.status();
-  let response_text = response.text().await.expect("read response data");
-
-  if response_status != StatusCode::OK {
-    log::error!("bad response: {} - {:?}", response_status, response_text);
-    return Err("received non-200 response from Twitch");
-  }
-
-  let response_data: ChannelResponseData = match serde_json::from_str(response_text.as_str()) {
-    Ok(v) => v,
-    Err(e) => {
-      log::error!("error: {:?}, data: {}", e, response_text);
-      return Err("error deserializing data");
-    }
-  };
-  if cfg!(debug_assertions) {
-    log::info!("response_data: {:?}", response_data);
+    }),
+  )
+  .await?;
+  if data.channel.is_none() {
+    return Err(ResolveError::NotFound(String::from(
+      "channel does not exist",
+    )));
   }
-  if response_data.data.channel.is_none() {
-    return Err("channel does not exist");
-  }
-  let channel = response_data.data.channel.unwrap();
+  let channel = data.channel.unwrap();
   if channel.stream.is_none() {
-    return Err("channel is not live");
+    return Err(ResolveError::NotLive(String::from("channel is not live")));
   }
   let stream = channel.stream.unwrap();
 
-  return Ok(vec![PlaylistItem {
+  return Ok(Playlist::from(vec![PlaylistItem {
     path: format!(
       "https://usher.ttvnw.net/api/channel/hls/{}.m3u8?allow_source=true&allow_audio_only=true&sig={}&token={}",
       channel_name,
@@ -362,10 +712,15 @@ async fn resolve_channel(channel_name: String) -> Result<Vec<PlaylistItem>, &'st
     description: None,
     artist: channel.display_name,
     genre: stream.game.map(|game| game.display_name),
-    date: Some(stream.created_at.replace("T", " ").replace("Z", "")),
+    date: parse_date(&stream.created_at),
     duration: None,
+    start_time: None,
+    variants: None,
     language: Some(stream.language),
-  }]);
+    kind: ItemKind::Live,
+    id: None,
+    expires_at: token_expires(&stream.playback_access_token),
+  }]));
 }
 
 async fn resolve_channel_videos(
@@ -373,146 +728,332 @@ async fn resolve_channel_videos(
   filter: String,
   sort: String,
   cursor: Option<String>,
-) -> Result<Vec<PlaylistItem>, &'static str> {
-  let q = json!({
-    "query": include_str!("twitch/channel_videos.gql"),
-    "variables": {
+) -> Result<Playlist, ResolveError> {
+  let data: ChannelVideosData = graphql(
+    include_str!("twitch/channel_videos.gql"),
+    json!({
       "login": channel_name,
       "type": filter_to_broadcast_type(filter.clone()),
       "sort": sort.to_uppercase(),
       "limit": 30,
       "cursor": cursor,
-    },
-  });
-  let request_data = serde_json::to_string(&q).unwrap();
-
-  let client = reqwest::Client::builder()
-    .build()
-    .expect("build reqwest client");
-  let client_id = crate::CONFIG.twitch_client_id.as_ref().unwrap().as_str();
-  let response = client
-    .post(GRAPHQL_URL)
-    .header("Client-ID", client_id)
-    .body(request_data)
-    .send()
-    .await
-    .expect("send graphql request");
-  let response_status = response.status();
-  let response_text = response.text().await.expect("read response data");
-
-  if response_status != StatusCode::OK {
-    log::error!("bad response: {} - {:?}", response_status, response_text);
-    return Err("received non-200 response from Twitch");
+    }),
+  )
+  .await?;
+  if data.user.is_none() {
+    return Err(ResolveError::NotFound(String::from("user does not exist")));
   }
+  let user = data.user.unwrap();
+  let last_cursor = user.videos.edges.last().map(|edge| edge.cursor.clone());
 
-  let response_data: ChannelVideosResponseData = match serde_json::from_str(response_text.as_str())
-  {
-    Ok(v) => v,
-    Err(e) => {
-      log::error!("error: {:?}, data: {}", e, response_text);
-      return Err("error deserializing data");
-    }
-  };
-  if cfg!(debug_assertions) {
-    log::info!("response_data: {:?}", response_data);
+  let mut playlist: Vec<_> = user
+    .videos
+    .edges
+    .into_iter()
+    .filter_map(|edge| {
+      // Videos without an id can't be linked to
+      let id = edge.node.id?;
+      return Some(PlaylistItem {
+        path: video_url(&id),
+        name: edge.node.title,
+        description: edge.node.description,
+        artist: Some(user.display_name.clone()),
+        genre: edge.node.game.map(|game| game.display_name),
+        date: parse_date(&edge.node.recorded_at),
+        duration: Some(parse_duration(edge.node.duration.as_str())),
+        start_time: None,
+        variants: None,
+        language: Some(edge.node.language),
+        kind: ItemKind::Vod,
+        id: Some(video_url(&id)),
+        expires_at: None,
+      });
+    })
+    .collect();
+
+  if let (true, Some(cursor)) = (user.videos.page_info.has_next_page, last_cursor) {
+    playlist.push(load_more(
+      format!(
+        "https://www.twitch.tv/{}/videos?filter={}&sort={}&cursor={}",
+        channel_name, filter, sort, cursor
+      ),
+      Some(user.display_name.clone()),
+    ));
   }
-  if response_data.data.user.is_none() {
-    return Err("user is null");
+
+  return Ok(Playlist {
+    title: Some(user.display_name.clone()),
+    description: Some(format!("Videos by {}", user.display_name)),
+    items: playlist,
+    variants: HashMap::new(),
+  });
+}
+
+async fn resolve_channel_clips(
+  channel_name: String,
+  range: String,
+  cursor: Option<String>,
+) -> Result<Playlist, ResolveError> {
+  let data: ChannelClipsData = graphql(
+    include_str!("twitch/channel_clips.gql"),
+    json!({
+      "login": channel_name,
+      "period": range_to_clips_period(&range),
+      "limit": 30,
+      "cursor": cursor,
+    }),
+  )
+  .await?;
+  if data.user.is_none() {
+    return Err(ResolveError::NotFound(String::from("user does not exist")));
   }
-  let user = response_data.data.user.unwrap();
-  let last_cursor = user.videos.edges.last().map(|edge| edge.cursor.clone());
+  let user = data.user.unwrap();
+  let last_cursor = user.clips.edges.last().map(|edge| edge.cursor.clone());
 
   let mut playlist: Vec<_> = user
-    .videos
+    .clips
     .edges
     .into_iter()
     .map(|edge| PlaylistItem {
-      path: format!(
-        "https://www.twitch.tv/videos/{}",
-        edge.node.id.unwrap().as_str()
-      ),
+      path: clip_url(&edge.node.slug),
       name: edge.node.title,
-      description: edge.node.description,
+      description: None,
       artist: Some(user.display_name.clone()),
       genre: edge.node.game.map(|game| game.display_name),
-      date: Some(edge.node.recorded_at.replace("T", " ").replace("Z", "")),
-      duration: Some(parse_duration(edge.node.duration.as_str())),
+      date: parse_date(&edge.node.created_at),
+      duration: Some(edge.node.duration_seconds),
+      start_time: None,
+      variants: None,
       language: Some(edge.node.language),
+      kind: ItemKind::Clip,
+      id: Some(clip_url(&edge.node.slug)),
+      expires_at: None,
     })
     .collect();
 
-  if user.videos.page_info.has_next_page {
-    playlist.push(PlaylistItem {
-      path: format!(
-        "https://www.twitch.tv/{}/videos?filter={}&sort={}&cursor={}",
-        channel_name,
-        filter,
-        sort,
-        last_cursor.unwrap()
+  if let (true, Some(cursor)) = (user.clips.page_info.has_next_page, last_cursor) {
+    playlist.push(load_more(
+      format!(
+        "https://www.twitch.tv/{}/clips?filter=clips&range={}&cursor={}",
+        channel_name, range, cursor
       ),
-      name: String::from("Load more"),
-      description: None,
-      artist: Some(user.display_name.clone()),
-      genre: None,
-      date: None,
-      duration: None,
-      language: None,
-    })
+      Some(user.display_name.clone()),
+    ));
   }
 
-  return Ok(playlist);
+  return Ok(Playlist {
+    title: Some(user.display_name.clone()),
+    description: Some(format!("Clips of {}", user.display_name)),
+    items: playlist,
+    variants: HashMap::new(),
+  });
 }
 
-async fn resolve_video(video_id: String) -> Result<Vec<PlaylistItem>, &'static str> {
-  let q = json!({
-    "query": include_str!("twitch/video.gql"),
-    "variables": {
-      "vodID": video_id,
-      "platform": "web",
-      "playerType": "site",
-    },
+async fn resolve_category_streams(
+  category: Category,
+  cursor: Option<String>,
+) -> Result<Playlist, ResolveError> {
+  let mut variables = category.variables();
+  variables["limit"] = json!(30);
+  variables["cursor"] = json!(cursor);
+  let data: CategoryStreamsData =
+    graphql(include_str!("twitch/category_streams.gql"), variables).await?;
+  if data.game.is_none() {
+    return Err(ResolveError::NotFound(String::from(
+      "category does not exist",
+    )));
+  }
+  let game = data.game.unwrap();
+  let last_cursor = game.streams.edges.last().map(|edge| edge.cursor.clone());
+
+  // Streams are sorted by viewers, the channels are resolved when they are played
+  let mut playlist: Vec<_> = game
+    .streams
+    .edges
+    .into_iter()
+    .filter_map(|edge| {
+      let broadcaster = edge.node.broadcaster?;
+      return Some(PlaylistItem {
+        path: channel_url(&broadcaster.login),
+        name: edge.node.title,
+        description: Some(format!("{} viewers", edge.node.viewers_count)),
+        artist: Some(broadcaster.display_name),
+        genre: Some(game.display_name.clone()),
+        date: parse_date(&edge.node.created_at),
+        duration: None,
+        start_time: None,
+        variants: None,
+        language: Some(edge.node.language),
+        kind: ItemKind::Live,
+        id: Some(channel_url(&broadcaster.login)),
+        expires_at: None,
+      });
+    })
+    .collect();
+
+  if let (true, Some(cursor)) = (game.streams.page_info.has_next_page, last_cursor) {
+    playlist.push(load_more(
+      format!("{}?cursor={}", category.url(), cursor),
+      None,
+    ));
+  }
+
+  return Ok(Playlist {
+    title: Some(game.display_name.clone()),
+    description: Some(format!("Live streams in {}", game.display_name)),
+    items: playlist,
+    variants: HashMap::new(),
   });
-  let request_data = serde_json::to_string(&q).unwrap();
-
-  let client = reqwest::Client::builder()
-    .build()
-    .expect("build reqwest client");
-  let client_id = crate::CONFIG.twitch_client_id.as_ref().unwrap().as_str();
-  let response = client
-    .post(GRAPHQL_URL)
-    .header("Client-ID", client_id)
-    .body(request_data)
-    .send()
-    .await
-    .expect("send graphql request");
-  let response_status = response.status();
-  let response_text = response.text().await.expect("read response data");
+}
 
-  if response_status != StatusCode::OK {
-    log::error!("bad response: {} - {:?}", response_status, response_text);
-    return Err("received non-200 response from Twitch");
+async fn resolve_category_clips(
+  category: Category,
+  range: String,
+  cursor: Option<String>,
+) -> Result<Playlist, ResolveError> {
+  let mut variables = category.variables();
+  variables["period"] = json!(range_to_clips_period(&range));
+  variables["limit"] = json!(30);
+  variables["cursor"] = json!(cursor);
+  let data: CategoryClipsData =
+    graphql(include_str!("twitch/category_clips.gql"), variables).await?;
+  if data.game.is_none() {
+    return Err(ResolveError::NotFound(String::from(
+      "category does not exist",
+    )));
   }
+  let game = data.game.unwrap();
+  let last_cursor = game.clips.edges.last().map(|edge| edge.cursor.clone());
 
-  let response_data: VideoResponseData = match serde_json::from_str(response_text.as_str()) {
-    Ok(v) => v,
-    Err(e) => {
-      log::error!("error: {:?}, data: {}", e, response_text);
-      return Err("error deserializing data");
-    }
+  let mut playlist: Vec<_> = game
+    .clips
+    .edges
+    .into_iter()
+    .map(|edge| PlaylistItem {
+      path: clip_url(&edge.node.slug),
+      name: edge.node.title,
+      description: None,
+      artist: edge.node.broadcaster.map(|user| user.display_name),
+      genre: Some(game.display_name.clone()),
+      date: parse_date(&edge.node.created_at),
+      duration: Some(edge.node.duration_seconds),
+      start_time: None,
+      variants: None,
+      language: Some(edge.node.language),
+      kind: ItemKind::Clip,
+      id: Some(clip_url(&edge.node.slug)),
+      expires_at: None,
+    })
+    .collect();
+
+  if let (true, Some(cursor)) = (game.clips.page_info.has_next_page, last_cursor) {
+    playlist.push(load_more(
+      format!("{}/clips?range={}&cursor={}", category.url(), range, cursor),
+      None,
+    ));
+  }
+
+  return Ok(Playlist {
+    title: Some(game.display_name.clone()),
+    description: Some(format!("Clips in {}", game.display_name)),
+    items: playlist,
+    variants: HashMap::new(),
+  });
+}
+
+async fn resolve_category_videos(
+  category: Category,
+  filter: String,
+  sort: String,
+  cursor: Option<String>,
+) -> Result<Playlist, ResolveError> {
+  let mut variables = category.variables();
+  // The directory uses singular filters, e.g. "archive" instead of "archives"
+  variables["types"] = match filter.as_str() {
+    "all" => json!(null),
+    filter => json!([filter.trim_end_matches('s').to_uppercase()]),
   };
-  if cfg!(debug_assertions) {
-    log::info!("response_data: {:?}", response_data);
+  variables["sort"] = json!(sort.to_uppercase());
+  variables["limit"] = json!(30);
+  variables["cursor"] = json!(cursor);
+  let data: CategoryVideosData =
+    graphql(include_str!("twitch/category_videos.gql"), variables).await?;
+  if data.game.is_none() {
+    return Err(ResolveError::NotFound(String::from(
+      "category does not exist",
+    )));
   }
-  if response_data.data.video.is_none() {
-    return Err("video is null");
+  let game = data.game.unwrap();
+  let last_cursor = game.videos.edges.last().map(|edge| edge.cursor.clone());
+
+  let mut playlist: Vec<_> = game
+    .videos
+    .edges
+    .into_iter()
+    .filter_map(|edge| {
+      // Videos without an id can't be linked to
+      let id = edge.node.id?;
+      return Some(PlaylistItem {
+        path: video_url(&id),
+        name: edge.node.title,
+        description: edge.node.description,
+        artist: edge.node.owner.map(|owner| owner.display_name),
+        genre: Some(game.display_name.clone()),
+        date: parse_date(&edge.node.recorded_at),
+        duration: Some(parse_duration(edge.node.duration.as_str())),
+        start_time: None,
+        variants: None,
+        language: Some(edge.node.language),
+        kind: ItemKind::Vod,
+        id: Some(video_url(&id)),
+        expires_at: None,
+      });
+    })
+    .collect();
+
+  if let (true, Some(cursor)) = (game.videos.page_info.has_next_page, last_cursor) {
+    playlist.push(load_more(
+      format!(
+        "{}/videos/{}?sort={}&cursor={}",
+        category.url(),
+        filter,
+        sort,
+        cursor
+      ),
+      None,
+    ));
+  }
+
+  return Ok(Playlist {
+    title: Some(game.display_name.clone()),
+    description: Some(format!("Videos in {}", game.display_name)),
+    items: playlist,
+    variants: HashMap::new(),
+  });
+}
+
+async fn resolve_video(video_id: String) -> Result<Playlist, ResolveError> {
+  let data: VideoData = graphql(
+    include_str!("twitch/video.gql"),
+    json!({
+      "vodID": video_id,
+      "platform": "web",
+      "playerType": "site",
+    }),
+  )
+  .await?;
+  if data.video.is_none() {
+    return Err(ResolveError::NotFound(String::from("video does not exist")));
   }
-  let video = response_data.data.video.unwrap();
+  let video = data.video.unwrap();
   if video.playback_access_token.is_none() {
-    return Err("playback_access_token is null");
+    return Err(ResolveError::Upstream(String::from(
+      "playback_access_token is null",
+    )));
   }
   let token = video.playback_access_token.unwrap();
 
-  return Ok(vec![PlaylistItem {
+  return Ok(Playlist::from(vec![PlaylistItem {
     path: format!(
       "https://usher.ttvnw.net/vod/{}.m3u8?allow_source=true&allow_audio_only=true&sig={}&token={}",
       video_id,
@@ -523,69 +1064,46 @@ async fn resolve_video(video_id: String) -> Result<Vec<PlaylistItem>, &'static s
     description: video.description,
     artist: video.owner.map(|owner| owner.display_name),
     genre: video.game.map(|game| game.display_name),
-    date: Some(video.recorded_at.replace("T", " ").replace("Z", "")),
+    date: parse_date(&video.recorded_at),
     duration: Some(parse_duration(video.duration.as_str())),
+    start_time: None,
+    variants: None,
     language: Some(video.language),
-  }]);
+    kind: ItemKind::Vod,
+    id: Some(video_url(&video_id)),
+    expires_at: token_expires(&token),
+  }]));
 }
 
-async fn resolve_clip(slug: String) -> Result<Vec<PlaylistItem>, &'static str> {
-  let q = json!({
-    "query": include_str!("twitch/clip.gql"),
-    "variables": {
+async fn resolve_clip(slug: String) -> Result<Playlist, ResolveError> {
+  let data: ClipData = graphql(
+    include_str!("twitch/clip.gql"),
+    json!({
       "slug": slug,
       "platform": "web",
       "playerType": "site",
-    },
-  });
-  let request_data = serde_json::to_string(&q).unwrap();
-
-  let client = reqwest::Client::builder()
-    .build()
-    .expect("build reqwest client");
-  let client_id = crate::CONFIG.twitch_client_id.as_ref().unwrap().as_str();
-  let response = client
-    .post(GRAPHQL_URL)
-    .header("Client-ID", client_id)
-    .body(request_data)
-    .send()
-    .await
-    .expect("send graphql request");
-  let response_status = response.status();
-  let response_text = response.text().await.expect("read response data");
-
-  if response_status != StatusCode::OK {
-    log::error!("bad response: {} - {:?}", response_status, response_text);
-    return Err("received non-200 response from Twitch");
+    }),
+  )
+  .await?;
+  if data.clip.is_none() {
+    return Err(ResolveError::NotFound(String::from("clip does not exist")));
   }
-
-  let response_data: ClipResponseData = match serde_json::from_str(response_text.as_str()) {
-    Ok(v) => v,
-    Err(e) => {
-      log::error!("error: {:?}, data: {}", e, response_text);
-      return Err("error deserializing data");
-    }
-  };
-  if cfg!(debug_assertions) {
-    log::info!("response_data: {:?}", response_data);
-  }
-  if response_data.data.clip.is_none() {
-    return Err("clip is null");
-  }
-  let clip = response_data.data.clip.unwrap();
+  let clip = data.clip.unwrap();
   let token_value: ClipTokenValue =
     match serde_json::from_str(clip.playback_access_token.value.as_str()) {
       Ok(v) => v,
       Err(e) => {
         log::error!("error: {:?}", e);
-        return Err("error deserializing token_value");
+        return Err(ResolveError::Deserialize(String::from(
+          "error deserializing token_value",
+        )));
       }
     };
   if cfg!(debug_assertions) {
     log::info!("token_value: {:?}", token_value);
   }
 
-  return Ok(vec![PlaylistItem {
+  return Ok(Playlist::from(vec![PlaylistItem {
     path: format!(
       "{}?allow_source=true&allow_audio_only=true&sig={}&token={}",
       token_value.clip_uri,
@@ -596,10 +1114,131 @@ async fn resolve_clip(slug: String) -> Result<Vec<PlaylistItem>, &'static str> {
     description: None,
     artist: Some(clip.broadcaster.display_name),
     genre: clip.game.map(|game| game.display_name),
-    date: Some(clip.created_at.replace("T", " ").replace("Z", "")),
+    date: parse_date(&clip.created_at),
     duration: Some(clip.duration_seconds),
+    start_time: None,
+    variants: None,
     language: Some(clip.language),
-  }]);
+    kind: ItemKind::Clip,
+    id: Some(clip_url(&slug)),
+    expires_at: token_value.expires,
+  }]));
+}
+
+// Sends a GraphQL query to Twitch and returns the data part of the response
+async fn graphql<T: DeserializeOwned + Debug>(
+  query: &str,
+  variables: serde_json::Value,
+) -> Result<T, ResolveError> {
+  let request_data = json!({
+    "query": query,
+    "variables": variables,
+  });
+
+  let client_id = client_id()?;
+  let response = client::send(
+    "graphql",
+    client::CLIENT
+      .post(GRAPHQL_URL)
+      .header("Client-ID", client_id)
+      .body(serde_json::to_string(&request_data).unwrap()),
+  )
+  .await?;
+  let response_status = response.status();
+  let retry_after = response
+    .headers()
+    .get("Retry-After")
+    .and_then(|v| v.to_str().ok())
+    .and_then(|v| v.parse::<u64>().ok());
+  let response_text = match response.text().await {
+    Ok(v) => v,
+    Err(e) => {
+      log::error!("error reading response: {:?}", e);
+      return Err(ResolveError::Upstream(String::from(
+        "error reading response from Twitch",
+      )));
+    }
+  };
+
+  if response_status != StatusCode::OK {
+    log::error!("bad response: {} - {:?}", response_status, response_text);
+    return Err(classify_status(
+      response_status,
+      &response_text,
+      retry_after,
+    ));
+  }
+
+  let response_data: GraphQLResponse<T> = match serde_json::from_str(response_text.as_str()) {
+    Ok(v) => v,
+    Err(e) => {
+      log::error!("error: {:?}, data: {}", e, response_text);
+      return Err(ResolveError::Deserialize(String::from(
+        "error deserializing data",
+      )));
+    }
+  };
+  if cfg!(debug_assertions) {
+    log::info!("response_data: {:?}", response_data);
+  }
+
+  if !response_data.errors.is_empty() {
+    let messages: Vec<_> = response_data
+      .errors
+      .iter()
+      .map(|error| error.message.as_str())
+      .collect();
+    log::error!("graphql errors: {:?}", messages);
+    if response_data.data.is_none() {
+      return Err(ResolveError::Upstream(format!(
+        "Twitch returned an error: {}",
+        messages.join(", ")
+      )));
+    }
+  }
+
+  match response_data.data {
+    Some(data) => Ok(data),
+    None => Err(ResolveError::Upstream(String::from(
+      "Twitch returned no data",
+    ))),
+  }
+}
+
+fn classify_status(status: StatusCode, body: &str, retry_after: Option<u64>) -> ResolveError {
+  if status == StatusCode::TOO_MANY_REQUESTS {
+    return ResolveError::RateLimited(retry_after);
+  }
+  // An invalid Client-ID results in a 400 or 401 response that mentions the header
+  if status == StatusCode::UNAUTHORIZED
+    || status == StatusCode::FORBIDDEN
+    || (status == StatusCode::BAD_REQUEST && body.contains("Client-ID"))
+  {
+    return ResolveError::Misconfigured(String::from(
+      "Twitch rejected the configured twitch_client_id",
+    ));
+  }
+  return ResolveError::Upstream(format!("received {} response from Twitch", status.as_u16()));
+}
+
+// The token value is a JSON string that contains its own expiry time
+fn token_expires(token: &PlaybackAccessToken) -> Option<u64> {
+  match serde_json::from_str::<TokenValue>(token.value.as_str()) {
+    Ok(v) => v.expires,
+    Err(e) => {
+      log::warn!("error deserializing token value: {:?}", e);
+      None
+    }
+  }
+}
+
+fn client_id() -> Result<&'static str, ResolveError> {
+  match crate::CONFIG.twitch_client_id.as_ref() {
+    Some(client_id) => Ok(client_id.as_str()),
+    None => Err(ResolveError::Misconfigured(String::from(
+      "twitch_client_id has not been configured",
+    ))),
+  }
 }
 
 // all => None, archives => ARCHIVE, highlights => HIGHLIGHT, uploads => UPLOAD
@@ -613,6 +1252,81 @@ fn filter_to_broadcast_type(filter: String) -> Option<String> {
   return Some(broadcast_type);
 }
 
+// The range in the URL is the one from Twitch's clips page, which defaults to 7d. Unknown ranges are normalized
+// here, so that they don't get their own cache entries and aren't repeated in the "Load more" URL.
+fn clips_range(range: Option<&str>) -> String {
+  let range = range.unwrap_or_default().to_lowercase();
+  match range.as_str() {
+    "24h" | "7d" | "30d" | "all" => range,
+    _ => String::from("7d"),
+  }
+}
+
+// Takes a range from clips_range
+fn range_to_clips_period(range: &str) -> &'static str {
+  match range {
+    "24h" => "LAST_DAY",
+    "30d" => "LAST_MONTH",
+    "all" => "ALL_TIME",
+    _ => "LAST_WEEK",
+  }
+}
+
+// The "Load more" item at the end of a listing that links to the next page
+fn load_more(path: String, artist: Option<String>) -> PlaylistItem {
+  return PlaylistItem {
+    path,
+    name: String::from("Load more"),
+    description: None,
+    artist,
+    genre: None,
+    date: None,
+    duration: None,
+    start_time: None,
+    variants: None,
+    language: None,
+    kind: ItemKind::Pagination,
+    id: None,
+    expires_at: None,
+  };
+}
+
+fn channel_url(channel_name: &str) -> String {
+  return format!("https://www.twitch.tv/{}", channel_name);
+}
+
+fn video_url(video_id: &str) -> String {
+  return format!("https://www.twitch.tv/videos/{}", video_id);
+}
+
+fn clip_url(slug: &str) -> String {
+  return format!("https://clips.twitch.tv/{}", slug);
+}
+
+fn parse_date(s: &str) -> Option<DateTime<Utc>> {
+  match DateTime::parse_from_rfc3339(s) {
+    Ok(date) => Some(date.to_utc()),
+    Err(e) => {
+      log::error!("parse_date({}) error: {}", s, e);
+      None
+    }
+  }
+}
+
+// https://www.twitch.tv/videos/113837699?t=1h2m3s
+// https://player.twitch.tv/?video=v113837699&time=1h2m3s
+fn start_time(url: &str) -> Option<usize> {
+  static START_TIME_PATTERN: OnceLock<Regex> = OnceLock::new();
+  let re = START_TIME_PATTERN.get_or_init(|| Regex::new(r"[?&](?:t|time)=([0-9hms]+)").unwrap());
+  let t = re.captures(url)?.get(1)?.as_str();
+  // Plain numbers are seconds
+  let seconds = t.parse::<usize>().unwrap_or_else(|_| parse_duration(t));
+  if seconds == 0 {
+    return None;
+  }
+  return Some(seconds);
+}
+
 fn parse_duration(s: &str) -> usize {
   let mut seconds = 0;
   let mut numbers = String::with_capacity(2);
@@ -665,5 +1379,100 @@ mod tests {
     // Not seen on Twitch so they don't work properly:
     assert_eq!(parse_duration("1d8h47m50s"), 67670);
     assert_eq!(parse_duration("1y10d"), 0);
+
+    assert_eq!(
+      start_time("https://www.twitch.tv/videos/113837699?t=1h2m3s"),
+      Some(3723)
+    );
+    assert_eq!(
+      start_time("https://www.twitch.tv/videos/113837699?t=90"),
+      Some(90)
+    );
+    assert_eq!(
+      start_time("https://player.twitch.tv/?video=v113837699&parent=example.com&time=2m"),
+      Some(120)
+    );
+    assert_eq!(
+      start_time("https://www.twitch.tv/videos/113837699?t=0s"),
+      None
+    );
+    assert_eq!(start_time("https://www.twitch.tv/videos/113837699"), None);
+  }
+
+  #[test]
+  fn test_probe() {
+    assert_eq!(
+      probe("https://www.twitch.tv/speedgaming/clips?filter=clips&range=1y"),
+      Some(TwitchMatch::ChannelClips(
+        String::from("speedgaming"),
+        String::from("7d"),
+        None
+      ))
+    );
+    assert_eq!(
+      probe("https://www.twitch.tv/directory/category/super-mario-64/clips?range=ALL"),
+      Some(TwitchMatch::CategoryClips(
+        Category::Slug(String::from("super-mario-64")),
+        String::from("all"),
+        None
+      ))
+    );
+    assert_eq!(
+      TwitchMatch::ChannelClips(String::from("speedgaming"), String::from("30d"), None).key(),
+      r#"{"ChannelClips":["speedgaming","30d",null]}"#
+    );
+    assert_ne!(
+      TwitchMatch::CategoryStreams(Category::Slug(String::from("super-mario-64")), None).key(),
+      TwitchMatch::CategoryStreams(Category::Name(String::from("super-mario-64")), None).key()
+    );
+    // The start time isn't part of the match, so every timestamp shares the cached video
+    assert_eq!(
+      probe("https://www.twitch.tv/videos/113837699?t=1h2m3s"),
+      Some(TwitchMatch::Video(String::from("113837699")))
+    );
+    assert_eq!(
+      Twitch.start_time("https://www.twitch.tv/videos/113837699?t=1h2m3s"),
+      Some(3723)
+    );
+    assert_eq!(
+      Twitch.start_time("https://www.twitch.tv/gamesdonequick/videos?t=1h2m3s"),
+      None
+    );
+    assert_eq!(
+      probe("https://www.twitch.tv/SpeedGaming/clips?filter=clips&range=30d"),
+      Some(TwitchMatch::ChannelClips(
+        String::from("speedgaming"),
+        String::from("30d"),
+        None
+      ))
+    );
+    assert_eq!(
+      probe("https://www.twitch.tv/directory/category/super-mario-64?cursor=abc"),
+      Some(TwitchMatch::CategoryStreams(
+        Category::Slug(String::from("super-mario-64")),
+        Some(String::from("abc"))
+      ))
+    );
+    assert_eq!(
+      probe("https://www.twitch.tv/directory/game/Super%20Mario%2064/clips"),
+      Some(TwitchMatch::CategoryClips(
+        Category::Name(String::from("Super Mario 64")),
+        String::from("7d"),
+        None
+      ))
+    );
+    assert_eq!(
+      probe("https://www.twitch.tv/directory/category/super-mario-64/videos/archive?sort=views"),
+      Some(TwitchMatch::CategoryVideos(
+        Category::Slug(String::from("super-mario-64")),
+        String::from("archive"),
+        String::from("views"),
+        None
+      ))
+    );
+    assert_eq!(
+      Category::Name(String::from("Super Mario 64")).url(),
+      "https://www.twitch.tv/directory/game/Super%20Mario%2064"
+    );
   }
 }
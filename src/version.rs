@@ -0,0 +1,104 @@
+use crate::error::ResolveError;
+use crate::{ItemKind, PlaylistItem};
+
+// The version of the twitch.lua script served at /vlc/twitch.lua
+pub const CLIENT_VERSION: u32 = 2;
+
+// Versions above this are recorded as "other" in the metrics, since the v parameter is controlled by the client
+const MAX_METRICS_VERSION: u32 = 100;
+
+// The v parameter is sent by twitch.lua, requests without it are not from a versioned client
+pub fn parse(v: Option<&str>) -> Option<u32> {
+  return v.and_then(|v| v.trim().parse::<u32>().ok());
+}
+
+// The label of the v parameter in the client version metrics
+pub fn metrics_label(v: Option<&str>) -> String {
+  match (v, parse(v)) {
+    (None, _) => String::from("none"),
+    (Some(_), Some(version)) if version <= MAX_METRICS_VERSION => version.to_string(),
+    (Some(_), _) => String::from("other"),
+  }
+}
+
+// Clients older than min_client_version are no longer supported
+pub fn check(version: Option<u32>) -> Result<(), ResolveError> {
+  return check_version(version, crate::CONFIG.min_client_version);
+}
+
+// Clients older than recommended_client_version still work, but get a notice appended to their playlists
+pub fn notice(version: Option<u32>, base_url: &str) -> Option<PlaylistItem> {
+  return notice_item(
+    version,
+    crate::CONFIG.recommended_client_version,
+    &upgrade_url(base_url),
+  );
+}
+
+fn upgrade_url(base_url: &str) -> String {
+  return crate::CONFIG
+    .upgrade_url
+    .clone()
+    .unwrap_or_else(|| format!("{}{}", base_url, crate::vlc::TWITCH_LUA_PATH));
+}
+
+fn check_version(version: Option<u32>, min_version: u32) -> Result<(), ResolveError> {
+  match version {
+    Some(version) if version < min_version => Err(ResolveError::UpgradeRequired(format!(
+      "Please upgrade your twitch.lua file! Version {} is no longer supported.",
+      version
+    ))),
+    _ => Ok(()),
+  }
+}
+
+fn notice_item(version: Option<u32>, recommended_version: u32, url: &str) -> Option<PlaylistItem> {
+  let version = version.filter(|version| *version < recommended_version)?;
+  return Some(PlaylistItem {
+    path: url.to_string(),
+    name: String::from("Please upgrade your twitch.lua file!"),
+    description: Some(format!(
+      "You are using version {} of twitch.lua, please upgrade to version {} or later. Download it from {}",
+      version, recommended_version, url
+    )),
+    language: None,
+    artist: None,
+    genre: None,
+    date: None,
+    duration: None,
+    start_time: None,
+    variants: None,
+    kind: ItemKind::Notice,
+    id: None,
+    expires_at: None,
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test() {
+    assert_eq!(parse(Some("2")), Some(2));
+    assert_eq!(parse(Some("x")), None);
+    assert_eq!(parse(None), None);
+    assert_eq!(metrics_label(Some("2")), "2");
+    assert_eq!(metrics_label(Some("1000")), "other");
+    assert_eq!(metrics_label(Some("x")), "other");
+    assert_eq!(metrics_label(None), "none");
+
+    assert!(check_version(None, 2).is_ok());
+    assert!(check_version(Some(2), 2).is_ok());
+    assert!(matches!(
+      check_version(Some(1), 2),
+      Err(ResolveError::UpgradeRequired(_))
+    ));
+
+    assert!(notice_item(None, 3, "https://example.com/").is_none());
+    assert!(notice_item(Some(3), 3, "https://example.com/").is_none());
+    let item = notice_item(Some(2), 3, "https://example.com/").unwrap();
+    assert_eq!(item.kind, ItemKind::Notice);
+    assert_eq!(item.path, "https://example.com/");
+  }
+}
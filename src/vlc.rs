@@ -0,0 +1,103 @@
+use actix_web::{get, http::header, HttpRequest, HttpResponse};
+
+use crate::resolver;
+use crate::version::CLIENT_VERSION;
+
+const TWITCH_LUA: &str = include_str!("vlc/twitch.lua");
+
+pub const TWITCH_LUA_PATH: &str = "/vlc/twitch.lua";
+
+// The VLC playlist script that talks to /resolve, configured for this server
+#[get("/vlc/twitch.lua")]
+async fn twitch_lua(req: HttpRequest) -> HttpResponse {
+  let script = render(&crate::base_url(&req));
+  let etag = etag(&script);
+
+  let if_none_match = req
+    .headers()
+    .get(header::IF_NONE_MATCH)
+    .and_then(|v| v.to_str().ok());
+  // If-None-Match uses the weak comparison, so W/ prefixes are ignored
+  if if_none_match.is_some_and(|v| {
+    v.split(',')
+      .map(|tag| tag.trim().trim_start_matches("W/"))
+      .any(|tag| tag == etag || tag == "*")
+  }) {
+    return HttpResponse::NotModified()
+      .append_header((header::ETAG, etag))
+      .finish();
+  }
+
+  return HttpResponse::Ok()
+    .content_type("text/x-lua; charset=utf-8")
+    .append_header((header::ETAG, etag))
+    .append_header((
+      header::CONTENT_DISPOSITION,
+      "attachment; filename=\"twitch.lua\"",
+    ))
+    .body(script);
+}
+
+fn render(base_url: &str) -> String {
+  // Every host that any resolver handles, so that the script doesn't have to be updated when URL patterns are added
+  let hosts: Vec<_> = resolver::resolvers()
+    .iter()
+    .flat_map(|resolver| resolver.hosts().iter())
+    .map(|host| lua_string(host))
+    .collect();
+  return TWITCH_LUA
+    .replace("{{base_url_string}}", &lua_string(base_url))
+    .replace("{{base_url}}", base_url)
+    .replace(
+      "{{script_url}}",
+      &format!("{}{}", base_url, TWITCH_LUA_PATH),
+    )
+    .replace("{{version}}", &CLIENT_VERSION.to_string())
+    .replace("{{hosts}}", &hosts.join(", "));
+}
+
+// FNV-1a, since DefaultHasher can change between Rust releases and the ETag has to survive a rebuild
+fn etag(script: &str) -> String {
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for byte in script.bytes() {
+    hash ^= u64::from(byte);
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+  return format!("\"{:016x}\"", hash);
+}
+
+fn lua_string(s: &str) -> String {
+  let mut escaped = String::from("\"");
+  for c in s.chars() {
+    match c {
+      '\\' => escaped.push_str("\\\\"),
+      '"' => escaped.push_str("\\\""),
+      '\n' => escaped.push_str("\\n"),
+      '\r' => escaped.push_str("\\r"),
+      c if c.is_control() => {}
+      c => escaped.push(c),
+    }
+  }
+  escaped.push('"');
+  return escaped;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test() {
+    let script = render("https://example.com");
+    assert!(script.contains("local base_url = \"https://example.com\"\n"));
+    assert!(script.contains(&format!("local version = {}\n", CLIENT_VERSION)));
+    assert!(script.contains("\"www.twitch.tv\""));
+    assert!(script.contains("https://example.com/vlc/twitch.lua"));
+    assert!(!script.contains("{{"));
+    assert_eq!(etag(&script), etag(&render("https://example.com")));
+    assert_ne!(etag(&script), etag(&render("http://localhost:8080")));
+    assert_eq!(etag(""), "\"cbf29ce484222325\"");
+    assert_eq!(etag("a"), "\"af63dc4c8601ec8c\"");
+    assert_eq!(lua_string("a\"b\\c"), "\"a\\\"b\\\\c\"");
+  }
+}
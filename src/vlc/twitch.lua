@@ -0,0 +1,79 @@
+--[[
+  Resolves Twitch URLs using media-resolver ({{base_url}}).
+
+  Installation:
+    Put this file in the lua/playlist/ directory of your VLC installation or user directory:
+      - Windows: %APPDATA%\vlc\lua\playlist\
+      - macOS: ~/Library/Application Support/org.videolan.vlc/lua/playlist/
+      - Linux: ~/.local/share/vlc/lua/playlist/
+
+  Updates:
+    Download the latest version from {{script_url}}
+--]]
+
+local base_url = {{base_url_string}}
+local version = {{version}}
+local hosts = { {{hosts}} }
+
+function probe()
+  if vlc.access ~= "http" and vlc.access ~= "https" then
+    return false
+  end
+  local host = string.lower(string.match(vlc.path, "^([^/?#]+)") or "")
+  for _, h in ipairs(hosts) do
+    if host == h then
+      return true
+    end
+  end
+  return false
+end
+
+function parse()
+  local url = vlc.access .. "://" .. vlc.path
+  local resolve_url = base_url .. "/resolve?output=json&v=" .. version .. "&url=" .. vlc.strings.encode_uri_component(url)
+  local stream = vlc.stream(resolve_url)
+  if not stream then
+    vlc.msg.err("twitch.lua: could not connect to " .. base_url)
+    return {}
+  end
+
+  local chunks = {}
+  while true do
+    local chunk = stream:read(65536)
+    if not chunk or #chunk == 0 then
+      break
+    end
+    table.insert(chunks, chunk)
+  end
+
+  local json = require("dkjson")
+  local data, _, err = json.decode(table.concat(chunks))
+  if not data then
+    vlc.msg.err("twitch.lua: invalid response: " .. tostring(err))
+    return {}
+  end
+  if data.error then
+    vlc.msg.err("twitch.lua: " .. data.error)
+    return {}
+  end
+
+  local playlist = {}
+  for _, item in ipairs(data.items) do
+    local options = {}
+    if item.start_time then
+      table.insert(options, "start-time=" .. item.start_time.seconds)
+    end
+    table.insert(playlist, {
+      path = item.path,
+      name = item.name,
+      description = item.description,
+      language = item.language,
+      artist = item.artist,
+      genre = item.genre,
+      date = item.date,
+      duration = item.duration and item.duration.seconds,
+      options = options,
+    })
+  end
+  return playlist
+end